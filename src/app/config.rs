@@ -8,6 +8,7 @@ use crate::events::EventBus;
 use crate::models::{AppConfig, RequestDataTypes, RequestEvent, SendEvent, WsEvent};
 use crate::websocket::WebSocketHandler;
 use crate::app::get_app_state;
+use crate::cvat::{DeltaConfig, FilterConfig};
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -16,16 +17,18 @@ use once_cell::sync::OnceCell;
 // 전역 Config 상태 관리
 static CONFIG: OnceCell<ConfigManager> = OnceCell::new();
 
+type ConfigHandler = Box<dyn Fn(&AppConfig, &AppConfig) + Send + Sync>;
+
 pub struct ConfigManager {
     config: Arc<RwLock<AppConfig>>,
-    handlers: Arc<RwLock<Vec<Box<dyn Fn(&AppConfig, &AppConfig) + Send + Sync>>>>,
+    handlers: Arc<RwLock<Vec<ConfigHandler>>>,
 }
 
 impl ConfigManager {
     pub fn global() -> &'static ConfigManager {
         CONFIG.get_or_init(|| {
             let config = init_config()
-                .and_then(|c| Ok(c.try_deserialize::<AppConfig>().unwrap()))
+                .map(|c| c.try_deserialize::<AppConfig>().unwrap())
                 .unwrap_or_default();
             ConfigManager {
                 config: Arc::new(RwLock::new(config)),
@@ -102,12 +105,17 @@ pub fn init_config() -> Result<Config, std::io::Error> {
         Ok(config) => {
             state.capture_interval.store(config.get_int("capture_interval").unwrap() as u32, Ordering::Release);
             state.capture_delay_on_error.store(config.get_int("capture_delay_on_error").unwrap() as u32, Ordering::Release);
+            if let Ok(app_config) = config.clone().try_deserialize::<AppConfig>() {
+                state.set_filter_config(FilterConfig::from(&app_config));
+                state.set_delta_config(DeltaConfig::from(&app_config));
+                state.set_allowed_origins(app_config.allowed_origins);
+            }
             Ok(config.clone())
         },
         Err(e) => {
             log::error!("Config File: 로드 실패");
             log::error!("Error: {}", e);
-            Err(std::io::Error::new(std::io::ErrorKind::Other, e))
+            Err(std::io::Error::other(e))
         }
     }
 }
@@ -116,23 +124,15 @@ pub fn init_config() -> Result<Config, std::io::Error> {
 pub fn create_config_file_if_not_exist(target_path: &PathBuf) -> Result<(), std::io::Error>{
     if !target_path.exists() {
         log::debug!("Config File: 생성");
-        let app_config = AppConfig {
-            auto_app_update: true,
-            auto_lib_update: true,
-            capture_interval: 250,
-            capture_delay_on_error: 1000,
-            use_bit_blt_capture_mode: false
-        };
+        let app_config = AppConfig::default();
 
         let contents = serde_json::to_string_pretty(&app_config);
         match contents {
-            Ok(contents) => {
-                return std::fs::write(target_path, contents);
-            },
-            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
+            Ok(contents) => std::fs::write(target_path, contents),
+            Err(e) => Err(std::io::Error::other(e)),
         }
     } else {
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -156,14 +156,20 @@ pub async fn register_events(_event_bus: &Arc<EventBus>, ws_handler: &Arc<WebSoc
         let ws_handler = ws_handler_set.clone();
         async move {
             log::debug!("Set Config Event");
-            let config = match &params.data {
-                Some(RequestDataTypes::AppConfig(data)) => data.clone(),
-                Some(_) => return Err("Invalid config data type".into()),
-                None => return Err("Config data is required".into())
+            let patch = match (&params.data, &params.raw_data) {
+                (Some(RequestDataTypes::AppConfig(_)), Some(serde_json::Value::Object(patch))) => patch.clone(),
+                (Some(_), _) => return Err("Invalid config data type".into()),
+                (None, _) => return Err("Config data is required".into())
             };
+            // 다른 곳에서 바뀐 값을 덮어쓰지 않도록 잠금 안에서 합친다.
+            let mut merged = None;
             ConfigManager::global().update(|c| {
-                *c = config.clone();
+                merged = Some(merge_config(c, &patch));
+                if let Some(Ok(config)) = &merged {
+                    *c = config.clone();
+                }
             }).await?;
+            let config = merged.ok_or("Config was not updated")??;
             ws_handler.send_to(id.clone(), SendEvent::from(WsEvent::Config { 
                 config, 
                 id: id.clone() 
@@ -175,6 +181,42 @@ pub async fn register_events(_event_bus: &Arc<EventBus>, ws_handler: &Arc<WebSoc
     Ok(())
 }
 
+// setConfig로 바꿀 수 없는 보안 관련 항목. 앱의 확인 창이나 config.json을 직접 고쳐서만 바꿀 수 있다.
+const LOCKED_FIELDS: &[&str] = &[
+    "allowed_origins",
+    "prompt_unknown_origin",
+    "publish_enabled",
+    "release_source",
+    "release_manifest_url",
+    "release_local_dir",
+    "require_update_signature",
+    // 이전 버전이나 프리릴리즈로 내려받게 할 수 없도록 채널과 고정 버전도 잠근다.
+    "app_update_channel",
+    "app_pinned_version",
+    "lib_update_channel",
+    "lib_pinned_version",
+];
+
+/*
+ * 요청에 들어있는 항목만 현재 설정에 덮어쓴다. 빠진 항목은 기본값이 아니라 현재 값을 유지한다.
+ * LOCKED_FIELDS는 getConfig로 받은 설정을 그대로 돌려보내는 경우를 위해 현재 값과 같을 때만 허용한다.
+ */
+pub fn merge_config(current: &AppConfig, patch: &serde_json::Map<String, serde_json::Value>) -> Result<AppConfig, Box<dyn Error + Send + Sync>> {
+    let mut merged = serde_json::to_value(current)?;
+    if let serde_json::Value::Object(fields) = &mut merged {
+        for (key, value) in patch {
+            if LOCKED_FIELDS.contains(&key.as_str()) {
+                if fields.get(key) != Some(value) {
+                    return Err(format!("{} 항목은 setConfig로 바꿀 수 없습니다.", key).into());
+                }
+                continue;
+            }
+            fields.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(serde_json::from_value(merged)?)
+}
+
 // 설정 파일 저장
 pub fn save_config(app_config: &AppConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
     let target_dir = path::get_app_path();
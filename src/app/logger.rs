@@ -0,0 +1,93 @@
+use crate::models::LogInfo;
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::{Mutex, RwLock};
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+
+static LOGGER: Lazy<AppLogger> = Lazy::new(AppLogger::new);
+
+// log topic으로 보내기 위해 쌓아둘 수 있는 로그 수. 가득 차면 새 로그를 버린다.
+const LOG_CHANNEL_SIZE: usize = 256;
+
+/*
+ * 전역 logger. debug 모드에서는 log4rs로 파일과 표준 출력에 기록하고,
+ * 모드와 관계없이 GPA가 남긴 info 이상의 로그를 log topic을 구독한 클라이언트에게 보낸다.
+ * websocket 모듈의 로그는 보내지 않는다. 전송 실패 로그가 다시 전송되는 것을 막기 위함이다.
+ */
+pub struct AppLogger {
+    inner: RwLock<Option<log4rs::Logger>>,
+    sender: Mutex<Option<mpsc::Sender<LogInfo>>>,
+}
+
+impl AppLogger {
+    fn new() -> Self {
+        Self {
+            inner: RwLock::new(None),
+            sender: Mutex::new(None),
+        }
+    }
+
+    fn forwards(metadata: &Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+            && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && !metadata.target().starts_with(concat!(env!("CARGO_CRATE_NAME"), "::websocket"))
+    }
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        AppLogger::forwards(metadata) || self.inner.read().as_ref().is_some_and(|inner| inner.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(inner) = self.inner.read().as_ref() {
+            inner.log(record);
+        }
+        if !AppLogger::forwards(record.metadata()) {
+            return;
+        }
+        if let Some(sender) = self.sender.lock().as_ref() {
+            let time = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            let _ = sender.try_send(LogInfo {
+                level: record.level().as_str().to_lowercase(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+                time,
+            });
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = self.inner.read().as_ref() {
+            inner.flush();
+        }
+    }
+}
+
+// 전역 logger로 등록한다. 이미 등록했다면 아무것도 하지 않는다.
+pub fn init() -> Result<(), log::SetLoggerError> {
+    static INSTALLED: OnceCell<()> = OnceCell::new();
+    if INSTALLED.get().is_none() {
+        log::set_logger(&*LOGGER)?;
+        log::set_max_level(LevelFilter::Info);
+        let _ = INSTALLED.set(());
+    }
+    Ok(())
+}
+
+// debug 모드에서 기록할 log4rs logger를 설정한다.
+pub fn set_file_logger(logger: log4rs::Logger) {
+    log::set_max_level(logger.max_log_level().max(LevelFilter::Info));
+    *LOGGER.inner.write() = Some(logger);
+}
+
+// log topic으로 보낼 로그를 받는다. 마지막으로 호출한 곳만 받는다.
+pub fn subscribe() -> mpsc::Receiver<LogInfo> {
+    let (tx, rx) = mpsc::channel(LOG_CHANNEL_SIZE);
+    *LOGGER.sender.lock() = Some(tx);
+    rx
+}
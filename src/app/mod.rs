@@ -1,11 +1,17 @@
 pub mod config;
+#[cfg(windows)]
 pub mod installer;
+pub mod logger;
 pub mod updater;
+pub mod verify;
 pub mod path;
+pub mod release;
 
+#[cfg(windows)]
 mod tray;
 mod utils;
 
+#[cfg(windows)]
 pub use self::tray::*;
 pub use self::utils::*;
 
@@ -22,3 +28,6 @@ static APP_STATE: Lazy<AppState> = Lazy::new(AppState::new);
 pub fn get_app_state() -> &'static AppState {
     &APP_STATE
 }
+
+#[cfg(test)]
+mod test;
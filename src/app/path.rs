@@ -3,6 +3,12 @@ use std::path::PathBuf;
 use directories::{BaseDirs, ProjectDirs};
 
 pub fn get_app_path() -> PathBuf {
+    // 테스트는 사용자의 앱 디렉토리(페어링, 설정, 감사 기록) 대신 프로세스마다 임시 디렉토리를 사용한다.
+    if cfg!(test) {
+        let dir = std::env::temp_dir().join(format!("gpa-app-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        return dir;
+    }
     let base_dir = BaseDirs::new().unwrap().data_local_dir().to_path_buf();
     match ProjectDirs::from("com", "genshin-paisitioning", "") {
         Some(proj_dirs) => base_dir.join(proj_dirs.project_path()).to_path_buf(),
@@ -11,6 +17,9 @@ pub fn get_app_path() -> PathBuf {
 }
 
 pub fn get_cache_path() -> PathBuf {
+    if cfg!(test) {
+        return get_app_path().join("cache");
+    }
     match ProjectDirs::from("com", "genshin-paisitioning", "") {
         Some(proj_dirs) => proj_dirs.cache_dir().to_path_buf(),
         None => PathBuf::new(),
@@ -21,6 +30,10 @@ pub fn get_logs_path() -> PathBuf {
     get_app_path().join("logs")
 }
 
+pub fn get_recordings_path() -> PathBuf {
+    get_app_path().join("recordings")
+}
+
 /* pub fn get_current_exe_path() -> String {
     match std::env::current_exe() {
         Ok(current_exe) => {
@@ -43,4 +56,32 @@ pub fn get_current_work_directory() -> PathBuf {
 
 pub fn get_lib_path() -> PathBuf {
     get_app_path().join("cvAutoTrack")
-}
\ No newline at end of file
+}
+
+// 업데이트할 라이브러리를 받아 검증하는 디렉토리. get_lib_path()와 같은 위치에 두어 rename으로 교체할 수 있도록 한다.
+// 태그 이름과 관계없이 아래의 다른 디렉토리와 겹치지 않도록 staging- 을 붙인다.
+pub fn get_lib_staging_path(version: &str) -> PathBuf {
+    let version: String = version.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    get_app_path().join(format!("cvAutoTrack.staging-{}", version))
+}
+
+// 업데이트 전에 사용하던 라이브러리. rollbackLib으로 되돌릴 수 있다.
+pub fn get_lib_backup_path() -> PathBuf {
+    get_app_path().join("cvAutoTrack.previous")
+}
+
+// 되돌리는 동안 현재 라이브러리를 잠시 옮겨두는 디렉토리
+pub fn get_lib_restoring_path() -> PathBuf {
+    get_app_path().join("cvAutoTrack.restoring")
+}
+
+pub fn get_pairings_path() -> PathBuf {
+    get_app_path().join("pairings.json")
+}
+
+// 로그 디렉토리는 디버그 모드 시작 시 비워지므로, 감사 기록은 앱 디렉토리에 둔다.
+pub fn get_publish_audit_path() -> PathBuf {
+    get_app_path().join("publish-audit.jsonl")
+}
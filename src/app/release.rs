@@ -0,0 +1,292 @@
+use crate::app::path;
+use crate::models::{AppConfig, ReleaseSourceKind, UpdateChannel};
+use log::debug;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/*
+ * 업데이트 대상 릴리즈 하나.
+ * GitHub releases API의 응답 형식을 그대로 따르므로, 매니페스트 파일도 같은 형식으로 작성하면 된다.
+ * 매니페스트는 릴리즈 하나 또는 릴리즈의 배열일 수 있다.
+ * {
+ *   "tag_name": "v1.2.3",
+ *   "name": "1.2.3",
+ *   "published_at": "2024-01-01T00:00:00Z",
+ *   "prerelease": false,
+ *   "assets": [{ "name": "gpa.zip", "browser_download_url": "gpa.zip" }]
+ * }
+ * browser_download_url이 상대 경로라면 매니페스트의 위치를 기준으로 한다.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub published_at: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+// 매니페스트 파일의 내용
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    List(Vec<Release>),
+    Single(Release),
+}
+
+impl From<Manifest> for Vec<Release> {
+    fn from(manifest: Manifest) -> Self {
+        match manifest {
+            Manifest::List(releases) => releases,
+            Manifest::Single(release) => vec![release],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    // "sha256:<hex>" 형식의 해시. 없으면 <name>.sha256 첨부 파일로 검증한다.
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+impl Release {
+    // 상대 경로로 적힌 첨부 파일 주소를 base 기준의 절대 주소로 바꾼다.
+    fn resolve_assets(&mut self, base: &Url) {
+        for asset in self.assets.iter_mut() {
+            match base.join(&asset.browser_download_url) {
+                Ok(url) => asset.browser_download_url = url.to_string(),
+                Err(e) => log::error!("첨부 파일 주소 해석 실패: {} ({})", asset.browser_download_url, e),
+            }
+        }
+    }
+
+    fn published_time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(&self.published_at).ok()
+    }
+
+    // 태그가 같은지 비교한다. 앞의 v는 무시한다.
+    fn is_tag(&self, tag: &str) -> bool {
+        self.tag_name.trim_start_matches('v') == tag.trim().trim_start_matches('v')
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReleaseCache {
+    timestamp: u64,
+    // 캐시를 만든 소스의 주소. 설정이 바뀌면 캐시를 쓰지 않는다.
+    source: String,
+    data: Vec<Release>,
+}
+
+impl ReleaseCache {
+    fn is_valid(&self, source: &str) -> bool {
+        // 2시간 이내의 캐시만 유효
+        self.source == source && now().saturating_sub(self.timestamp) < 7200
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/*
+ * 릴리즈 정보를 가져올 곳. AppConfig.release_source로 선택한다.
+ * - Github: api.github.com의 releases 목록
+ * - Manifest: GitHub가 느린 지역을 위한 미러. 정적 JSON 파일의 URL
+ * - Local: 디렉토리의 {repo}/release.json. 업데이트를 로컬에서 시험할 때 사용한다.
+ */
+#[derive(Debug, Clone)]
+pub enum ReleaseSource {
+    Github,
+    Manifest { url: String },
+    Local { dir: PathBuf },
+}
+
+impl ReleaseSource {
+    pub fn from_config(config: &AppConfig) -> Self {
+        match config.release_source {
+            ReleaseSourceKind::Github => ReleaseSource::Github,
+            ReleaseSourceKind::Manifest => ReleaseSource::Manifest {
+                url: config.release_manifest_url.clone(),
+            },
+            // 상대 경로는 앱 디렉토리 기준
+            ReleaseSourceKind::Local => ReleaseSource::Local {
+                dir: path::get_app_path().join(&config.release_local_dir),
+            },
+        }
+    }
+
+    // 채널에 맞는 릴리즈를 고른다.
+    pub async fn select(
+        &self,
+        owner: &str,
+        repo: &str,
+        channel: UpdateChannel,
+        pinned_version: &str,
+        force: bool,
+    ) -> Result<Release, Box<dyn Error + Send + Sync>> {
+        let releases = match (self, channel) {
+            // 고정된 버전은 최근 릴리즈 목록에 없을 수 있으므로 태그로 직접 찾는다.
+            (ReleaseSource::Github, UpdateChannel::Pinned) => github_tag(owner, repo, pinned_version, force).await?,
+            _ => self.releases(owner, repo, force).await?,
+        };
+        select_release(releases, channel, pinned_version)
+            .ok_or_else(|| match channel {
+                UpdateChannel::Pinned => format!("{}에서 고정된 버전 '{}'을 찾을 수 없습니다.", repo, pinned_version).into(),
+                _ => format!("{}에서 {:?} 채널의 릴리즈를 찾을 수 없습니다.", repo, channel).into(),
+            })
+    }
+
+    pub async fn releases(&self, owner: &str, repo: &str, force: bool) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+        debug!("ReleaseSource::releases({:?}, {}/{})", self, owner, repo);
+        let (kind, source) = match self {
+            ReleaseSource::Github => ("github", format!("https://api.github.com/repos/{}/{}/releases?per_page=50", owner, repo)),
+            ReleaseSource::Manifest { url } => {
+                if url.is_empty() {
+                    return Err("release_manifest_url이 설정되지 않았습니다.".into());
+                }
+                ("manifest", url.replace("{owner}", owner).replace("{repo}", repo))
+            }
+            // 로컬 파일은 캐시하지 않는다.
+            ReleaseSource::Local { dir } => return load_local(&dir.join(repo).join("release.json")),
+        };
+        fetch_cached(kind, owner, repo, &source, force).await
+    }
+}
+
+// GitHub의 /releases/tags/{tag}로 릴리즈 하나를 가져온다. 태그에 v가 붙어있는지 알 수 없으므로 둘 다 시도한다.
+async fn github_tag(owner: &str, repo: &str, tag: &str, force: bool) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+    let tag = tag.trim().trim_start_matches('v');
+    if tag.is_empty() {
+        return Err(format!("{}의 고정할 버전이 설정되지 않았습니다.", repo).into());
+    }
+    let source = |tag: &str| format!("https://api.github.com/repos/{}/{}/releases/tags/{}", owner, repo, tag);
+    match fetch_cached("github_tag", owner, repo, &source(&format!("v{}", tag)), force).await {
+        Ok(releases) => Ok(releases),
+        Err(e) => {
+            debug!("v{} 태그를 찾을 수 없습니다: {}", tag, e);
+            fetch_cached("github_tag", owner, repo, &source(tag), force).await
+        }
+    }
+}
+
+async fn fetch_cached(kind: &str, owner: &str, repo: &str, source: &str, force: bool) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+    // 캐시 확인
+    let cache_path = get_cache_file_path(kind, owner, repo);
+    if !force {
+        if let Some(cached) = load_from_cache(&cache_path, source)? {
+            debug!("Using cached release info");
+            return Ok(cached);
+        }
+    }
+
+    let releases = fetch_remote(source).await?;
+    // 응답 캐시에 저장
+    save_to_cache(&cache_path, source, &releases)?;
+    Ok(releases)
+}
+
+/*
+ * - Stable: 프리릴리즈가 아닌 가장 최근 릴리즈
+ * - Beta: 프리릴리즈를 포함한 가장 최근 릴리즈
+ * - Pinned: 태그가 pinned_version인 릴리즈
+ * 초안(draft)은 고르지 않는다.
+ */
+pub fn select_release(releases: Vec<Release>, channel: UpdateChannel, pinned_version: &str) -> Option<Release> {
+    let mut candidates = releases.into_iter().filter(|r| !r.draft);
+    match channel {
+        UpdateChannel::Pinned => candidates.find(|r| r.is_tag(pinned_version)),
+        UpdateChannel::Beta => newest(candidates),
+        UpdateChannel::Stable => newest(candidates.filter(|r| !r.prerelease)),
+    }
+}
+
+// 게시 시간이 가장 늦은 릴리즈. 시간이 같거나 없다면 목록의 앞에 있는 것을 고른다.
+fn newest(releases: impl Iterator<Item = Release>) -> Option<Release> {
+    releases.fold(None, |newest: Option<Release>, release| {
+        let is_newer = newest.as_ref()
+            .is_none_or(|n| release.published_time() > n.published_time());
+        if is_newer { Some(release) } else { newest }
+    })
+}
+
+async fn fetch_remote(url: &str) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+    let response = client.get(url)
+        .header("User-Agent", "reqwest")
+        .header("Accept", "application/vnd.github.v3+json")
+        .send()
+        .await?;
+
+    log::debug!("{:#?}", &response.status());
+    if response.status().as_u16() != 200 {
+        let e = format!("Error: 릴리즈 정보 요청에 실패했습니다 ({}): {}", url, &response.text().await?);
+        return Err(e.into());
+    }
+
+    let manifest: Manifest = serde_json::from_str(&response.text().await?)?;
+    let mut releases: Vec<Release> = manifest.into();
+    let base = Url::parse(url)?;
+    releases.iter_mut().for_each(|r| r.resolve_assets(&base));
+    Ok(releases)
+}
+
+fn load_local(manifest: &PathBuf) -> Result<Vec<Release>, Box<dyn Error + Send + Sync>> {
+    let contents = std::fs::read_to_string(manifest)
+        .map_err(|e| format!("릴리즈 파일을 읽을 수 없습니다 ({:?}): {}", manifest, e))?;
+    let mut releases: Vec<Release> = serde_json::from_str::<Manifest>(&contents)?.into();
+    let base = Url::from_file_path(manifest)
+        .map_err(|_| format!("릴리즈 파일 경로가 올바르지 않습니다: {:?}", manifest))?;
+    releases.iter_mut().for_each(|r| r.resolve_assets(&base));
+    Ok(releases)
+}
+
+fn get_cache_file_path(kind: &str, owner: &str, repo: &str) -> PathBuf {
+    path::get_cache_path().join(format!("{}_{}_{}.cache", kind, owner, repo))
+}
+
+fn save_to_cache(cache_path: &PathBuf, source: &str, data: &[Release]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let cache = ReleaseCache {
+        timestamp: now(),
+        source: source.to_string(),
+        data: data.to_vec(),
+    };
+
+    std::fs::create_dir_all(cache_path.parent().unwrap())?;
+    std::fs::write(cache_path, serde_json::to_string(&cache)?)?;
+    Ok(())
+}
+
+fn load_from_cache(cache_path: &PathBuf, source: &str) -> Result<Option<Vec<Release>>, Box<dyn Error + Send + Sync>> {
+    if !cache_path.exists() {
+        return Ok(None);
+    }
+
+    let cache_str = std::fs::read_to_string(cache_path)?;
+    // 이전 형식의 캐시 파일은 무시한다.
+    let cache: ReleaseCache = match serde_json::from_str(&cache_str) {
+        Ok(cache) => cache,
+        Err(_) => return Ok(None),
+    };
+
+    if cache.is_valid(source) {
+        Ok(Some(cache.data))
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,668 @@
+#[cfg(test)]
+mod config_tests {
+    use crate::app::config::merge_config;
+    use crate::app::verify::SignaturePolicy;
+    use crate::models::{AppConfig, ReleaseSourceKind};
+    use serde_json::json;
+
+    fn patch(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().expect("patch must be an object").clone()
+    }
+
+    #[test]
+    fn merge_keeps_omitted_fields() {
+        let current = AppConfig {
+            auto_app_update: !AppConfig::default().auto_app_update,
+            capture_interval: 100,
+            ..Default::default()
+        };
+        let merged = merge_config(&current, &patch(json!({ "capture_interval": 50 }))).unwrap();
+        assert_eq!(merged.capture_interval, 50);
+        assert_eq!(merged.auto_app_update, current.auto_app_update);
+        assert_eq!(merged, AppConfig { capture_interval: 50, ..current });
+    }
+
+    #[test]
+    fn merge_rejects_invalid_value() {
+        let current = AppConfig::default();
+        assert!(merge_config(&current, &patch(json!({ "capture_interval": "fast" }))).is_err());
+    }
+
+    #[test]
+    fn merge_rejects_locked_fields() {
+        let current = AppConfig::default();
+        for field in [
+            json!({ "allowed_origins": ["https://evil.example"] }),
+            json!({ "prompt_unknown_origin": !current.prompt_unknown_origin }),
+            json!({ "publish_enabled": !current.publish_enabled }),
+            json!({ "release_source": "manifest" }),
+            json!({ "release_manifest_url": "https://evil.example/release.json" }),
+            json!({ "release_local_dir": "C:/evil" }),
+            json!({ "require_update_signature": !current.require_update_signature }),
+            json!({ "app_update_channel": "pinned" }),
+            json!({ "app_pinned_version": "v0.0.1" }),
+            json!({ "lib_update_channel": "beta" }),
+            json!({ "lib_pinned_version": "v0.0.1" }),
+        ] {
+            assert!(merge_config(&current, &patch(field)).is_err());
+        }
+    }
+
+    #[test]
+    fn merge_accepts_unchanged_locked_fields() {
+        let current = AppConfig::default();
+        let mut echoed = patch(serde_json::to_value(&current).unwrap());
+        echoed.insert("capture_interval".to_string(), json!(77));
+        let merged = merge_config(&current, &echoed).unwrap();
+        assert_eq!(merged.capture_interval, 77);
+        assert_eq!(merged.allowed_origins, current.allowed_origins);
+    }
+
+    #[test]
+    fn signature_optional_unless_required() {
+        // 릴리즈 소스와 관계없이 require_update_signature를 켰을 때만 서명을 요구한다.
+        for release_source in [ReleaseSourceKind::Github, ReleaseSourceKind::Manifest, ReleaseSourceKind::Local] {
+            assert!(!SignaturePolicy::from_config(&AppConfig { release_source, ..Default::default() }).required);
+        }
+        assert!(SignaturePolicy::from_config(&AppConfig { require_update_signature: true, ..Default::default() }).required);
+    }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use crate::app::release::{Release, ReleaseAsset, ReleaseSource};
+    use crate::app::updater::download_file;
+    use crate::app::verify::{find_md5, parse_hex, verify_asset, verify_signature, Checksum, SignaturePolicy};
+    use crate::models::{UpdateChannel, UpdateInfo};
+    use crate::websocket::WebSocketHandler;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::path::{Path, PathBuf};
+
+    // 형식만 맞는 임의의 해시
+    const SHA256: &str = "2c2cd7d8e5b3b4e0ba4f8f0b0d6e3cf4ab64c6b4d3c8f1b5e2e8aa6a2cd2d83b";
+    const MD5: &str = "b8dc56c1a0f5c0d1e7b5d3d2c7b6a3f1";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gpa-verify-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn asset(dir: &Path, name: &str) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: reqwest::Url::from_file_path(dir.join(name)).unwrap().to_string(),
+            digest: None,
+        }
+    }
+
+    #[test]
+    fn parse_hex_checks_length_and_digits() {
+        assert_eq!(parse_hex(&SHA256.to_uppercase(), 64).as_deref(), Some(SHA256));
+        assert_eq!(parse_hex(&format!(" {}\n", MD5), 32).as_deref(), Some(MD5));
+        assert!(parse_hex(MD5, 64).is_none());
+        assert!(parse_hex(&MD5.replace('b', "g"), 32).is_none());
+    }
+
+    #[test]
+    fn from_sidecar_reads_first_field() {
+        let sidecar = format!("{}  gpa.zip\n", SHA256);
+        assert_eq!(Checksum::from_sidecar(sidecar.as_bytes(), true), Some(Checksum::Sha256(SHA256.to_string())));
+        assert_eq!(Checksum::from_sidecar(MD5.as_bytes(), false), Some(Checksum::Md5(MD5.to_string())));
+        // 해시 종류와 길이가 맞지 않으면 실패한다.
+        assert!(Checksum::from_sidecar(MD5.as_bytes(), true).is_none());
+        assert!(Checksum::from_sidecar(b"", false).is_none());
+    }
+
+    #[test]
+    fn verify_signature_accepts_only_matching_signature() {
+        let dir = temp_dir("sig");
+        let path = dir.join("gpa.zip");
+        std::fs::write(&path, b"paimon").unwrap();
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        let signature = key.sign(b"paimon").to_bytes();
+
+        assert!(verify_signature(&path, &signature, &public_key).is_ok());
+        assert!(verify_signature(&path, hex::encode(signature).as_bytes(), &public_key).is_ok());
+
+        let forged = key.sign(b"not paimon").to_bytes();
+        assert!(verify_signature(&path, &forged, &public_key).is_err());
+        let other_key = hex::encode(SigningKey::from_bytes(&[8u8; 32]).verifying_key().to_bytes());
+        assert!(verify_signature(&path, &signature, &other_key).is_err());
+        assert!(verify_signature(&path, b"garbage", &public_key).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_asset_uses_matching_sidecar() {
+        let dir = temp_dir("sidecar");
+        let path = dir.join("gpa.zip");
+        let release = Release {
+            tag_name: "v1.0.0".to_string(),
+            name: String::new(),
+            published_at: String::new(),
+            prerelease: false,
+            draft: false,
+            assets: vec![asset(&dir, "other.zip.md5"), asset(&dir, "gpa.zip"), asset(&dir, "gpa.zip.md5")],
+        };
+        let zip = &release.assets[1];
+        let md5 = hex::encode(<md5::Md5 as sha2::Digest>::digest(b"paimon"));
+
+        // 다른 파일의 .md5는 쓰지 않는다.
+        std::fs::write(dir.join("other.zip.md5"), &md5).unwrap();
+        std::fs::write(dir.join("gpa.zip.md5"), MD5).unwrap();
+        std::fs::write(&path, b"paimon").unwrap();
+        assert!(verify_asset(&release, zip, &path, true, &SignaturePolicy::default()).await.is_err());
+        assert!(!path.exists(), "unverified file must be removed");
+
+        std::fs::write(dir.join("gpa.zip.md5"), format!("{}  gpa.zip", md5)).unwrap();
+        std::fs::write(&path, b"paimon").unwrap();
+        assert!(verify_asset(&release, zip, &path, true, &SignaturePolicy::default()).await.is_ok());
+        // md5를 허용하지 않으면 해시가 없는 것으로 본다.
+        assert!(verify_asset(&release, zip, &path, false, &SignaturePolicy::default()).await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_asset_falls_back_to_only_md5() {
+        let dir = temp_dir("only-md5");
+        let path = dir.join("lib.zip");
+        let md5 = hex::encode(<md5::Md5 as sha2::Digest>::digest(b"paimon"));
+        std::fs::write(dir.join("cvAutoTrack.md5"), &md5).unwrap();
+        let mut release = Release {
+            tag_name: "v1.0.0".to_string(),
+            name: String::new(),
+            published_at: String::new(),
+            prerelease: false,
+            draft: false,
+            assets: vec![asset(&dir, "lib.zip"), asset(&dir, "cvAutoTrack.md5")],
+        };
+        let zip = release.assets[0].clone();
+        assert_eq!(find_md5(&release, &zip).map(|a| a.name.as_str()), Some("cvAutoTrack.md5"));
+
+        std::fs::write(&path, b"paimon").unwrap();
+        assert!(verify_asset(&release, &zip, &path, true, &SignaturePolicy::default()).await.is_ok());
+
+        // .md5가 여러 개라면 어느 것을 써야 할지 알 수 없다.
+        release.assets.push(asset(&dir, "other.md5"));
+        assert!(find_md5(&release, &zip).is_none());
+        assert!(verify_asset(&release, &zip, &path, true, &SignaturePolicy::default()).await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_source_signed_update() {
+        let dir = temp_dir("local");
+        let repo_dir = dir.join("repo");
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        let sha256 = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(b"paimon"));
+        std::fs::write(repo_dir.join("gpa.zip"), b"paimon").unwrap();
+        std::fs::write(repo_dir.join("gpa.zip.sha256"), format!("{}  gpa.zip", sha256)).unwrap();
+        std::fs::write(repo_dir.join("gpa.zip.sig"), hex::encode(key.sign(b"paimon").to_bytes())).unwrap();
+        std::fs::write(repo_dir.join("release.json"), r#"{
+            "tag_name": "v1.0.0",
+            "published_at": "2024-01-01T00:00:00Z",
+            "assets": [
+                { "name": "gpa.zip", "browser_download_url": "gpa.zip" },
+                { "name": "gpa.zip.sha256", "browser_download_url": "gpa.zip.sha256" },
+                { "name": "gpa.zip.sig", "browser_download_url": "gpa.zip.sig" }
+            ]
+        }"#).unwrap();
+
+        let source = ReleaseSource::Local { dir: dir.clone() };
+        let release = source.select("owner", "repo", UpdateChannel::Stable, "", false).await.unwrap();
+        let zip = release.assets.iter().find(|a| a.name == "gpa.zip").unwrap();
+        let dest = dir.join("download.zip");
+        let update_info = UpdateInfo {
+            target_type: "app".to_string(),
+            target_version: release.tag_name.clone(),
+            display_version_name: release.name.clone(),
+            current_version: String::new(),
+            downloaded: 0,
+            file_size: 0,
+            percent: 0.0,
+            done: false,
+            updated: false,
+        };
+        let download = || download_file(&zip.browser_download_url, &dest, WebSocketHandler::new(), update_info.clone(), "test".to_string());
+
+        let required = SignaturePolicy { public_key: Some(&public_key), required: true };
+        download().await.unwrap();
+        assert!(verify_asset(&release, zip, &dest, false, &required).await.is_ok());
+
+        // 다른 키로 검증하면 실패한다.
+        let other_key = hex::encode(SigningKey::from_bytes(&[8u8; 32]).verifying_key().to_bytes());
+        download().await.unwrap();
+        assert!(verify_asset(&release, zip, &dest, false, &SignaturePolicy { public_key: Some(&other_key), required: false }).await.is_err());
+
+        // 공개키가 없다면 서명을 요구할 때만 실패한다.
+        download().await.unwrap();
+        assert!(verify_asset(&release, zip, &dest, false, &SignaturePolicy { public_key: None, required: true }).await.is_err());
+        download().await.unwrap();
+        assert!(verify_asset(&release, zip, &dest, false, &SignaturePolicy::default()).await.is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod release_tests {
+    use crate::app::release::{select_release, Release, ReleaseSource};
+    use crate::app::updater::compare_versions;
+    use crate::models::UpdateChannel;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use warp::Filter;
+
+    fn release(tag: &str, published_at: &str, prerelease: bool, draft: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            name: tag.to_string(),
+            published_at: published_at.to_string(),
+            prerelease,
+            draft,
+            assets: Vec::new(),
+        }
+    }
+
+    fn releases() -> Vec<Release> {
+        vec![
+            release("v1.1.0", "2024-01-01T00:00:00Z", false, false),
+            release("v1.3.0", "2024-03-01T00:00:00Z", false, true),
+            release("v1.2.0-beta.1", "2024-02-01T00:00:00Z", true, false),
+            release("v1.0.0", "2023-12-01T00:00:00Z", false, false),
+        ]
+    }
+
+    fn tag(release: Option<Release>) -> Option<String> {
+        release.map(|r| r.tag_name)
+    }
+
+    #[test]
+    fn select_by_channel() {
+        assert_eq!(tag(select_release(releases(), UpdateChannel::Stable, "")).as_deref(), Some("v1.1.0"));
+        assert_eq!(tag(select_release(releases(), UpdateChannel::Beta, "")).as_deref(), Some("v1.2.0-beta.1"));
+        assert_eq!(tag(select_release(releases(), UpdateChannel::Pinned, "1.0.0")).as_deref(), Some("v1.0.0"));
+        // 초안은 고정된 버전이라도 고르지 않는다.
+        assert!(select_release(releases(), UpdateChannel::Pinned, "v1.3.0").is_none());
+        assert!(select_release(Vec::new(), UpdateChannel::Stable, "").is_none());
+    }
+
+    // 매니페스트 형식으로 쓴 releases()
+    fn manifest() -> serde_json::Value {
+        json!([
+            { "tag_name": "v1.1.0", "published_at": "2024-01-01T00:00:00Z",
+              "assets": [{ "name": "gpa.zip", "browser_download_url": "v1.1.0/gpa.zip" }] },
+            { "tag_name": "v1.2.0-beta.1", "published_at": "2024-02-01T00:00:00Z", "prerelease": true,
+              "assets": [{ "name": "gpa.zip", "browser_download_url": "https://mirror.example/gpa.zip" }] },
+        ])
+    }
+
+    #[tokio::test]
+    async fn local_source_resolves_relative_assets() {
+        let dir = std::env::temp_dir().join(format!("gpa-release-local-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("repo")).unwrap();
+        let source = ReleaseSource::Local { dir: dir.clone() };
+        assert!(source.select("owner", "repo", UpdateChannel::Stable, "", false).await.is_err());
+
+        std::fs::write(dir.join("repo").join("release.json"), manifest().to_string()).unwrap();
+        let stable = source.select("owner", "repo", UpdateChannel::Stable, "", false).await.unwrap();
+        assert_eq!(stable.tag_name, "v1.1.0");
+        let expected = reqwest::Url::from_file_path(dir.join("repo").join("v1.1.0").join("gpa.zip")).unwrap();
+        assert_eq!(stable.assets[0].browser_download_url, expected.to_string());
+
+        // 절대 주소는 그대로 둔다.
+        let beta = source.select("owner", "repo", UpdateChannel::Beta, "", false).await.unwrap();
+        assert_eq!(beta.assets[0].browser_download_url, "https://mirror.example/gpa.zip");
+        let pinned = source.select("owner", "repo", UpdateChannel::Pinned, "1.2.0-beta.1", false).await.unwrap();
+        assert_eq!(pinned.tag_name, "v1.2.0-beta.1");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn manifest_source_fetches_and_caches() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let route = warp::path!("mirror" / String / String / "release.json").map({
+            let hits = hits.clone();
+            move |_owner: String, _repo: String| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                warp::reply::json(&manifest())
+            }
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let source = ReleaseSource::Manifest {
+            url: format!("http://{}/mirror/{{owner}}/{{repo}}/release.json", addr),
+        };
+        let owner = format!("manifest-test-{}", std::process::id());
+        let stable = source.select(&owner, "repo", UpdateChannel::Stable, "", true).await.unwrap();
+        assert_eq!(stable.tag_name, "v1.1.0");
+        // 상대 주소는 매니페스트의 위치를 기준으로 한다.
+        assert_eq!(stable.assets[0].browser_download_url, format!("http://{}/mirror/{}/repo/v1.1.0/gpa.zip", addr, owner));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // 캐시가 있다면 다시 받지 않고, force라면 다시 받는다.
+        let beta = source.select(&owner, "repo", UpdateChannel::Beta, "", false).await.unwrap();
+        assert_eq!(beta.tag_name, "v1.2.0-beta.1");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        source.releases(&owner, "repo", true).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let missing = ReleaseSource::Manifest { url: format!("http://{}/missing.json", addr) };
+        assert!(missing.releases(&owner, "repo", true).await.is_err());
+        let empty = ReleaseSource::Manifest { url: String::new() };
+        assert!(empty.releases(&owner, "repo", true).await.is_err());
+    }
+
+    #[test]
+    fn compare_release_versions() {
+        assert!(compare_versions("1.2.0", "v1.2.0"));
+        assert!(compare_versions("1.10.0", "1.9.0"));
+        assert!(!compare_versions("1.9.0", "1.10.0"));
+        assert!(!compare_versions("", "1.0.0"));
+        // 정식 버전은 같은 번호의 프리릴리즈보다 새 버전이다.
+        assert!(compare_versions("1.2.0", "1.2.0-beta.1"));
+        assert!(!compare_versions("1.2.0-beta.1", "1.2.0"));
+    }
+
+    #[test]
+    fn compare_prerelease_identifiers() {
+        assert!(compare_versions("1.2.0-beta.10", "1.2.0-beta.9"));
+        assert!(!compare_versions("1.2.0-beta.9", "1.2.0-beta.10"));
+        assert!(compare_versions("1.2.0-beta", "1.2.0-alpha.5"));
+        assert!(compare_versions("1.2.0-beta.1", "1.2.0-beta"));
+        assert!(!compare_versions("1.2.0-1", "1.2.0-alpha"));
+    }
+}
+
+#[cfg(test)]
+mod lib_dir_tests {
+    use crate::app::updater::restore_lib_dirs;
+    use std::path::{Path, PathBuf};
+
+    struct Dirs {
+        root: PathBuf,
+        lib: PathBuf,
+        backup: PathBuf,
+        restoring: PathBuf,
+    }
+
+    impl Dirs {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("gpa-lib-dirs-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(&root).unwrap();
+            Dirs {
+                lib: root.join("cvAutoTrack"),
+                backup: root.join("cvAutoTrack.previous"),
+                restoring: root.join("cvAutoTrack.restoring"),
+                root,
+            }
+        }
+
+        fn restore(&self) {
+            restore_lib_dirs(&self.lib, &self.backup, &self.restoring).unwrap();
+        }
+    }
+
+    impl Drop for Dirs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn write_version(dir: &Path, version: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("version"), version).unwrap();
+    }
+
+    fn version(dir: &Path) -> Option<String> {
+        std::fs::read_to_string(dir.join("version")).ok()
+    }
+
+    #[test]
+    fn restores_backup_after_interrupted_update() {
+        let dirs = Dirs::new("update");
+        write_version(&dirs.backup, "old");
+        dirs.restore();
+        assert_eq!(version(&dirs.lib).as_deref(), Some("old"));
+        assert!(!dirs.backup.exists());
+    }
+
+    #[test]
+    fn restores_current_after_interrupted_rollback() {
+        // 현재 버전을 옮겨둔 뒤 종료
+        let dirs = Dirs::new("rollback");
+        write_version(&dirs.backup, "old");
+        write_version(&dirs.restoring, "new");
+        dirs.restore();
+        assert_eq!(version(&dirs.lib).as_deref(), Some("new"));
+        assert_eq!(version(&dirs.backup).as_deref(), Some("old"));
+        assert!(!dirs.restoring.exists());
+
+        // 이전 버전을 되돌린 뒤 종료
+        let dirs = Dirs::new("rollback-done");
+        write_version(&dirs.lib, "old");
+        write_version(&dirs.restoring, "new");
+        dirs.restore();
+        assert_eq!(version(&dirs.lib).as_deref(), Some("old"));
+        assert_eq!(version(&dirs.backup).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn keeps_complete_lib_dirs() {
+        let dirs = Dirs::new("complete");
+        write_version(&dirs.lib, "new");
+        write_version(&dirs.backup, "old");
+        dirs.restore();
+        assert_eq!(version(&dirs.lib).as_deref(), Some("new"));
+        assert_eq!(version(&dirs.backup).as_deref(), Some("old"));
+    }
+}
+
+#[cfg(test)]
+mod download_tests {
+    use crate::app::updater::{download_with, DownloadOptions};
+    use crate::models::{Encoding, Topic, UpdateInfo};
+    use crate::websocket::{Client, SendQueue, WebSocketHandler};
+    use parking_lot::Mutex;
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+    use warp::http::{Response, StatusCode};
+    use warp::hyper::Body;
+    use warp::Filter;
+
+    const ETAG: &str = "\"v1\"";
+    const STREAM_SIZE: usize = 2 * 1024 * 1024 + 100;
+
+    fn body() -> Vec<u8> {
+        (0..100_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    // 테스트 서버가 받은 요청
+    #[derive(Default)]
+    struct Requests {
+        count: AtomicUsize,
+        ranges: Mutex<Vec<Option<String>>>,
+    }
+
+    /*
+     * /file : ETag가 있고 If-Range가 맞을 때만 Range 요청에 206으로 답한다.
+     * /missing : 404
+     * /stream : Content-Length 없이 보낸다.
+     */
+    async fn serve(requests: Arc<Requests>) -> SocketAddr {
+        let file = warp::path!("file")
+            .and(warp::header::optional::<String>("range"))
+            .and(warp::header::optional::<String>("if-range"))
+            .map({
+                let requests = requests.clone();
+                move |range: Option<String>, if_range: Option<String>| {
+                    requests.count.fetch_add(1, Ordering::SeqCst);
+                    requests.ranges.lock().push(range.clone());
+                    let body = body();
+                    let start = range.as_deref()
+                        .and_then(|r| r.strip_prefix("bytes="))
+                        .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok())
+                        .filter(|_| if_range.as_deref() == Some(ETAG));
+                    let res = Response::builder().header("etag", ETAG);
+                    match start {
+                        Some(start) => res
+                            .status(StatusCode::PARTIAL_CONTENT)
+                            .header("content-range", format!("bytes {}-{}/{}", start, body.len() - 1, body.len()))
+                            .body(Body::from(body[start..].to_vec())),
+                        None => res.body(Body::from(body)),
+                    }.unwrap()
+                }
+            });
+        let missing = warp::path!("missing").map({
+            let requests = requests.clone();
+            move || {
+                requests.count.fetch_add(1, Ordering::SeqCst);
+                StatusCode::NOT_FOUND
+            }
+        });
+        let stream = warp::path!("stream").map(|| {
+            let chunks = vec![0u8; STREAM_SIZE].chunks(64 * 1024).map(|c| Ok::<_, std::io::Error>(c.to_vec())).collect::<Vec<_>>();
+            Response::new(Body::wrap_stream(futures::stream::iter(chunks)))
+        });
+        let (addr, server) = warp::serve(file.or(missing).or(stream)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        addr
+    }
+
+    fn options(retries: u32) -> DownloadOptions {
+        DownloadOptions {
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Some(Duration::from_secs(5)),
+            retries,
+        }
+    }
+
+    fn update_info() -> UpdateInfo {
+        UpdateInfo {
+            target_type: "app".to_string(),
+            target_version: "v1.0.0".to_string(),
+            display_version_name: String::new(),
+            current_version: String::new(),
+            downloaded: 0,
+            file_size: 0,
+            percent: 0.0,
+            done: false,
+            updated: false,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gpa-download-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn part_files(dest: &std::path::Path) -> (PathBuf, PathBuf) {
+        let part = PathBuf::from(format!("{}.part", dest.display()));
+        let validator = PathBuf::from(format!("{}.part.validator", dest.display()));
+        (part, validator)
+    }
+
+    #[tokio::test]
+    async fn resume_with_matching_validator() {
+        let requests = Arc::new(Requests::default());
+        let addr = serve(requests.clone()).await;
+        let dir = temp_dir("resume");
+        let dest = dir.join("gpa.zip");
+        let (part, validator) = part_files(&dest);
+        let url = format!("http://{}/file", addr);
+
+        std::fs::write(&part, &body()[..1000]).unwrap();
+        std::fs::write(&validator, ETAG).unwrap();
+        download_with(&url, &dest, &options(0), WebSocketHandler::new(), update_info(), "test".to_string()).await.unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body());
+        assert_eq!(requests.ranges.lock().as_slice(), [Some("bytes=1000-".to_string())]);
+        assert!(!part.exists() && !validator.exists());
+
+        // 서버의 파일이 바뀌었다면 처음부터 다시 받는다.
+        std::fs::write(&part, vec![0u8; 1000]).unwrap();
+        std::fs::write(&validator, "\"v0\"").unwrap();
+        download_with(&url, &dest, &options(0), WebSocketHandler::new(), update_info(), "test".to_string()).await.unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body());
+
+        // validator가 없으면 이어받지 않는다.
+        std::fs::write(&part, vec![0u8; 1000]).unwrap();
+        download_with(&url, &dest, &options(0), WebSocketHandler::new(), update_info(), "test".to_string()).await.unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body());
+        assert_eq!(requests.ranges.lock().last(), Some(&None));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn client_error_is_not_retried() {
+        let requests = Arc::new(Requests::default());
+        let addr = serve(requests.clone()).await;
+        let dir = temp_dir("missing");
+        let dest = dir.join("gpa.zip");
+
+        let url = format!("http://{}/missing", addr);
+        let err = download_with(&url, &dest, &options(3), WebSocketHandler::new(), update_info(), "test".to_string())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+        assert_eq!(requests.count.load(Ordering::SeqCst), 1);
+        assert!(!dest.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unknown_size_progress() {
+        let addr = serve(Arc::new(Requests::default())).await;
+        let dir = temp_dir("stream");
+        let dest = dir.join("gpa.zip");
+
+        // 진행 상황을 받을 클라이언트
+        let ws_handler = WebSocketHandler::new();
+        let queue = Arc::new(SendQueue::new(8));
+        ws_handler.clients.write().await.insert("test".to_string(), Client {
+            user_id: 0,
+            name: None,
+            kind: None,
+            version: None,
+            registered_at: SystemTime::now(),
+            connected_at: Some(SystemTime::now()),
+            disconnected_at: None,
+            stats: Default::default(),
+            sender: Some(queue.clone()),
+            topics: Topic::DEFAULT.into_iter().collect(),
+            protocol_version: 2,
+            encoding: Encoding::Json,
+        });
+
+        let url = format!("http://{}/stream", addr);
+        download_with(&url, &dest, &options(0), ws_handler, update_info(), "test".to_string()).await.unwrap();
+        assert_eq!(std::fs::metadata(&dest).unwrap().len(), STREAM_SIZE as u64);
+
+        // 크기를 모르면 1 MiB마다 받은 크기만 알린다.
+        let mut reported = Vec::new();
+        while queue.queued() > 0 {
+            let message = queue.pop().await.unwrap();
+            let event: serde_json::Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+            assert_eq!(event["event"], "update");
+            assert_eq!(event["data"]["fileSize"], 0);
+            assert_eq!(event["data"]["percent"], 0.0);
+            reported.push(event["data"]["downloaded"].as_u64().unwrap());
+        }
+        assert_eq!(reported.len(), 2);
+        assert!(reported[0] >= 1024 * 1024 && reported[1] >= 2 * 1024 * 1024, "{:?}", reported);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,7 +1,10 @@
 use std::sync::mpsc;
 use tray_item::TrayItem;
+use crate::views::confirm::{ask_dialog, confirm_dialog};
+use crate::websocket::PairingStore;
 
 enum Message {
+    RevokePairings,
     Quit,
 }
 
@@ -12,6 +15,13 @@ pub fn add_tray_item() {
 
     let (tx, rx) = mpsc::channel();
 
+    let revoke_tx = tx.clone();
+    tray.add_menu_item("연결된 사이트 해제", move || {
+        log::debug!("트레이로 부터 페어링 해제");
+        revoke_tx.send(Message::RevokePairings).unwrap();
+    })
+    .unwrap();
+
     tray.add_menu_item("종료", move || {
         log::debug!("트레이로 부터 종료");
         tx.send(Message::Quit).unwrap();
@@ -20,8 +30,26 @@ pub fn add_tray_item() {
 
     loop {
         match rx.recv() {
+            Ok(Message::RevokePairings) => revoke_pairings(),
             Ok(Message::Quit) => super::terminate_process(),
             _ => {}
         }
     }
 }
+
+fn revoke_pairings() {
+    let store = PairingStore::global();
+    let labels: Vec<String> = store.list().iter().map(|p| p.label()).collect();
+    if labels.is_empty() {
+        let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), "연결된 사이트가 없습니다.", false);
+        return;
+    }
+    let desc = format!("아래의 연결을 모두 해제하시겠습니까?\n{}", labels.join("\n"));
+    if !ask_dialog(env!("CARGO_PKG_DESCRIPTION"), &desc) {
+        return;
+    }
+    if let Err(e) = store.revoke_all() {
+        log::error!("Pairings: 저장 실패");
+        log::error!("Error: {}", e);
+    }
+}
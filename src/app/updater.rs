@@ -1,16 +1,18 @@
 use log::debug;
-use serde_json::Value;
 use crate::app::terminate_process;
-use crate::models::{AppConfig, AppEvent, RequestDataTypes, RequestEvent, SendEvent, WsEvent};
-use crate::models::UpdateInfo;
+use crate::models::{AppConfig, AppEvent, RequestEvent, SendEvent, WsEvent};
+use crate::models::{UpdateChannel, UpdateInfo};
 use crate::views::confirm::confirm_dialog;
 use crate::app::path;
+use crate::app::config::ConfigManager;
+use crate::app::release::{Release, ReleaseSource};
+use crate::app::verify::{is_sidecar, verify_asset, SignaturePolicy};
+use crate::cvat::{initialize_cvat, start_cvat, validate_library};
+use crate::app::get_app_state;
 use crate::websocket::WebSocketHandler;
 use std::collections::HashMap;
 use std::fs::{File, self};
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
-use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
 use crate::events::EventBus;
 use std::error::Error;
 use std::sync::Arc;
@@ -18,97 +20,16 @@ use futures::StreamExt;
 use reqwest::Client as StreamClient;
 use std::cmp::min;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct GithubCache {
-    timestamp: u64,
-    data: Value,
-}
-
-impl GithubCache {
-    fn is_valid(&self) -> bool {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        // 2시간 이내의 캐시만 유효
-        now - self.timestamp < 7200
-    }
-}
-
-fn get_cache_file_path(owner: &str, repo: &str) -> PathBuf {
-    path::get_cache_path().join(format!("github_{}_{}.cache", owner, repo))
-}
-
-fn save_to_cache(owner: &str, repo: &str, data: &Value) -> std::result::Result<(), Box<dyn Error + Send + Sync>> {
-    let cache = GithubCache {
-        timestamp: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-        data: data.clone(),
-    };
-    
-    let cache_path = get_cache_file_path(owner, repo);
-    let cache_dir = cache_path.parent().unwrap();
-    std::fs::create_dir_all(cache_dir)?;
-    
-    let cache_str = serde_json::to_string(&cache)?;
-    std::fs::write(cache_path, cache_str)?;
-    Ok(())
-}
-
-fn load_from_cache(owner: &str, repo: &str) -> std::result::Result<Option<Value>, Box<dyn Error + Send + Sync>> {
-    let cache_path = get_cache_file_path(owner, repo);
-    if !cache_path.exists() {
-        return Ok(None);
-    }
-
-    let cache_str = std::fs::read_to_string(cache_path)?;
-    let cache: GithubCache = serde_json::from_str(&cache_str)?;
-    
-    if cache.is_valid() {
-        Ok(Some(cache.data))
-    } else {
-        Ok(None)
-    }
-}
-
-async fn fetch_app_version_on_github(owner: &str, repo: &str, force: bool) -> Result<Value, Box<dyn Error + Send + Sync>> {
-    debug!("fetch_app_version_on_github");
-    
-    // 캐시 확인
-    if !force {
-        if let Some(cached_data) = load_from_cache(owner, repo)? {
-            debug!("Using cached GitHub API response");
-            return Ok(cached_data);
-        }
-    }
-
-    // 캐시가 없거나 만료된 경우 GitHub API 호출
-    let client = reqwest::Client::new();
-    let url = format!(
-        "https://api.github.com/repos/{}/{}/releases/latest",
-        owner, repo
-    );
-    let response = client.get(&url)
-        .header("User-Agent", "reqwest")
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
-        
-    log::debug!("{:#?}", &response.status());
-    if response.status().as_u16() != 200 {
-        let e = format!("Error: Github API 요청에 실패했습니다: {}", &response.text().await?);
-        return Err(e.into());
-    }
-    
-    let json: Value = serde_json::from_str(&response.text().await?)?;
-    
-    // 응답 캐시에 저장
-    save_to_cache(owner, repo, &json)?;
-    
-    Ok(json)
+// 설정된 소스에서 채널에 맞는 릴리즈 정보를 가져온다.
+async fn fetch_release(
+    config: &AppConfig,
+    channel: UpdateChannel,
+    pinned_version: &str,
+    owner: &str,
+    repo: &str,
+    force: bool
+) -> Result<Release, Box<dyn Error + Send + Sync>> {
+    ReleaseSource::from_config(config).select(owner, repo, channel, pinned_version, force).await
 }
 
 pub async fn download_app(
@@ -117,12 +38,14 @@ pub async fn download_app(
     force: bool
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     debug!("download_app");
-    let json: Value = fetch_app_version_on_github("Haytsir", "Genshin-Paisitioning-App", force).await?;
+    let config = ConfigManager::global().get().await;
+    let channel = config.app_update_channel;
+    let release = fetch_release(&config, channel, &config.app_pinned_version, "Haytsir", "Genshin-Paisitioning-App", force).await?;
     let cache_dir = path::get_cache_path();
     // 태그 이름 가져오
     let version = env!("CARGO_PKG_VERSION");
-    let release_name = &json["tag_name"].as_str().unwrap_or("")[1..];
-    let release_display_name = &json["name"].as_str().unwrap_or("");
+    let release_name = release.tag_name.trim_start_matches('v');
+    let release_display_name = &release.name;
 
     // 업데이트를 요청한 유저에게 보낼 update info 생성
     let mut update_info = UpdateInfo {
@@ -137,8 +60,12 @@ pub async fn download_app(
         updated: true
     };
 
-    // 버전 비교
-    if compare_versions(version, &json["tag_name"].as_str().unwrap_or("")) {
+    // 버전 비교. 고정된 버전은 현재 버전보다 낮더라도 그 버전으로 바꾼다.
+    let up_to_date = match channel {
+        UpdateChannel::Pinned => release.tag_name.trim_start_matches('v') == version,
+        _ => compare_versions(version, &release.tag_name),
+    };
+    if up_to_date {
         log::debug!("GPA가 최신 버전입니다. ({})", release_name);
         update_info.done = true;
         update_info.updated = false;
@@ -153,10 +80,9 @@ pub async fn download_app(
     }
 
     // 첨부 파일 가져오기
-    let assets = &json["assets"];
-    for asset in assets.as_array().unwrap() {
-        let asset_url = asset["browser_download_url"].as_str().unwrap();
-        let asset_name = asset["name"].as_str().unwrap();
+    for asset in &release.assets {
+        let asset_url = asset.browser_download_url.as_str();
+        let asset_name = asset.name.as_str();
         log::debug!("{} 다운로드 시도", asset_url);
         log::debug!("파일명: {}", asset_name);
         let ws_handler = ws_handler.clone();
@@ -175,11 +101,14 @@ pub async fn download_app(
                 requester_id.clone(),
             ).await?;
 
+            // 해시와 서명이 맞지 않으면 설치하지 않는다.
+            verify_asset(&release, asset, &arch_path, false, &SignaturePolicy::from_config(&config)).await?;
+
             tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
             // 파일 추출 및 처리
             let mut mappings = HashMap::new();
-            mappings.insert("exe", &cache_dir);
+            mappings.insert("exe", cache_dir.as_path());
             extract_files_from_zip(&arch_path, mappings)?;
 
             // 파일 정리 및 업데이트
@@ -187,8 +116,8 @@ pub async fn download_app(
             let exe_name = current_exe.file_name().unwrap();
 
             log::debug!("Updating...");
-            self_replace::self_replace(&cache_dir.join(exe_name))?;
-            fs::remove_file(&cache_dir.join(exe_name))?;
+            self_replace::self_replace(cache_dir.join(exe_name))?;
+            fs::remove_file(cache_dir.join(exe_name))?;
             
             let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), "GPA 업데이트를 완료했습니다.", false);
 
@@ -200,12 +129,12 @@ pub async fn download_app(
             terminate_process();
         }
     }
-    return Ok(());
+    Ok(())
 }
 
 pub async fn download_cvat(
     event_bus: &Arc<EventBus>,
-    ws_handler: WebSocketHandler, 
+    ws_handler: &Arc<WebSocketHandler>,
     requester_id: String, 
     force: bool
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -213,12 +142,14 @@ pub async fn download_cvat(
     let lib_path = path::get_lib_path();    
     let cache_dir = path::get_cache_path();
 
-    let json: Value = fetch_app_version_on_github("Haytsir", "gpa-lib-mirror", force).await?;
+    let config = ConfigManager::global().get().await;
+    let channel = config.lib_update_channel;
+    let release = fetch_release(&config, channel, &config.lib_pinned_version, "Haytsir", "gpa-lib-mirror", force).await?;
     
     // 태그 이름 가져오기
     let version = get_local_version(&lib_path);
-    let release_name = json["tag_name"].as_str().unwrap_or("");
-    let release_display_name = json["name"].as_str().unwrap_or("");
+    let release_name = release.tag_name.as_str();
+    let release_display_name = release.name.as_str();
 
     // 업데이트를 요한 유저에게 보낼 update info 생성
     let mut update_info = UpdateInfo {
@@ -236,14 +167,18 @@ pub async fn download_cvat(
     if lib_path.join("cvAutoTrack.dll").exists() {
         // 버전 비교, 최신 버전이 자가 더 낮은 경우가 있으니 파일 수정 시간으로 비교
         let last_file_modified = get_file_modified_time(&lib_path.join("cvAutoTrack.dll"))?;
-        let last_lib_published = parse_iso8601(json["published_at"].as_str().unwrap_or(""))?;
-        
-        if last_file_modified > last_lib_published/* compare_versions(&version, json["tag_name"].as_str().unwrap_or("")) */ {
+        let up_to_date = match channel {
+            // 고정된 버전은 설치된 버전의 태그로 비교한다.
+            UpdateChannel::Pinned => update_info.current_version.trim_start_matches('v') == release_name.trim_start_matches('v'),
+            _ => last_file_modified > parse_iso8601(&release.published_at)?,
+        };
+
+        if up_to_date/* compare_versions(&version, &release.tag_name) */ {
             log::debug!("CVAT가 최신 버전입니다. ({})", release_name);
             update_info.done = true;
             update_info.updated = false;
             // 처음 상황을 전송한다.
-            send_lib_update_info(ws_handler.clone(), requester_id.clone(), Some(update_info)).await?;
+            send_lib_update_info((**ws_handler).clone(), requester_id.clone(), Some(update_info)).await?;
             return Ok(());
         } else {
             log::debug!(
@@ -252,79 +187,218 @@ pub async fn download_cvat(
             );
         }
     }
+    // 새 라이브러리는 별도 디렉토리에 받아 검증한 뒤 교체한다. 실패하면 기존 라이브러리는 그대로 남는다.
+    let staging_path = path::get_lib_staging_path(release_name);
+    if staging_path.exists() {
+        fs::remove_dir_all(&staging_path)?;
+    }
+    fs::create_dir_all(&staging_path)?;
+    let staged = stage_cvat(&release, &config, &staging_path, &cache_dir, ws_handler, &requester_id, &update_info).await;
+    if let Err(e) = staged {
+        if let Err(e) = fs::remove_dir_all(&staging_path) {
+            log::debug!("Failed to remove staging dir: {}", e);
+        }
+        return Err(e);
+    }
+
+    let was_loaded = get_app_state().get_instance().is_some();
+    let was_tracking = get_app_state().is_tracking();
     log::debug!("CVAT 언로드 시도");
     event_bus.emit(&AppEvent::Uninit()).await?;
-    
+    let swapped = swap_lib_dirs(&staging_path, &lib_path, &path::get_lib_backup_path()).await;
+    // 교체에 실패했다면 기존 라이브러리가 되돌려져 있으므로, 어느 쪽이든 다시 로드한다.
+    reload_cvat(event_bus, ws_handler, was_loaded, was_tracking).await?;
+    swapped?;
+    log::debug!("CVAT 업데이트 완료. 이전 버전은 {:?}에 보관됩니다.", path::get_lib_backup_path());
+
+    update_info.done = true;
+    send_lib_update_info((**ws_handler).clone(), requester_id.clone(), Some(update_info)).await?;
+
+    Ok(())
+}
+
+// 릴리즈의 첨부 파일을 staging_path에 받고, 받은 라이브러리가 로드되는지 확인한다.
+async fn stage_cvat(
+    release: &Release,
+    config: &AppConfig,
+    staging_path: &Path,
+    cache_dir: &Path,
+    ws_handler: &WebSocketHandler,
+    requester_id: &str,
+    update_info: &UpdateInfo,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
     // 첨부 파일 처리
-    let assets = &json["assets"];
-    for asset in assets.as_array().unwrap() {
-        let asset_url = asset["browser_download_url"].as_str().unwrap();
-        let asset_name = asset["name"].as_str().unwrap();
+    for asset in &release.assets {
+        let asset_url = asset.browser_download_url.as_str();
+        let asset_name = asset.name.as_str();
+        // 해시와 서명은 verify_asset에서 받는다. md5는 아래에서 cvAutoTrack.md5로 함께 설치한다.
+        if is_sidecar(release, asset) && !asset_name.ends_with(".md5") {
+            continue;
+        }
         log::debug!("{} 다운로드 시도", asset_url);
         log::debug!("파일명: {}", asset_name);
-        let ws_handler = ws_handler.clone();
-        let update_info = update_info.clone();
 
-        // github에서 은 파일이 .zip 확장자인 경우
+        // github에서 받은 파일이 .zip 확장자인 경우
         if asset_name.ends_with(".zip") {
             let arch_path = cache_dir.join(asset_name);
-            let target_path = lib_path.clone();
-            
+
             // 파일 다운로드
             download_file(
                 asset_url,
                 &arch_path,
                 ws_handler.clone(),
                 update_info.clone(),
-                requester_id.clone(),
+                requester_id.to_string(),
             ).await?;
 
+            // 해시와 서명이 맞지 않으면 설치하지 않는다. 라이브러리 미러는 md5만 제공할 수 있다.
+            verify_asset(release, asset, &arch_path, true, &SignaturePolicy::from_config(config)).await?;
+
             tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
             // 파일 추출
             let mut mappings = HashMap::new();
-            mappings.insert("dll", &target_path);
-            std::fs::create_dir_all(lib_path.clone())?;
-            extract_files_from_zip(&arch_path, mappings)?;
-            
+            mappings.insert("dll", staging_path);
+            let extracted = extract_files_from_zip(&arch_path, mappings);
+
             // 임시 파일 정리
             if let Err(e) = std::fs::remove_file(&arch_path) {
                 log::debug!("Failed to remove temp file: {}", e);
             }
-            
-            // 파일 저장
-            let remove_res = std::fs::remove_file(cache_dir.join(asset_name));
-            match remove_res {
-                Ok(_) => {}
-                Err(e) => {
-                    log::debug!("{}", e);
-                }
-            }
+            extracted?;
         } else if asset_name.ends_with(".md5") || asset_name.ends_with(".tag") {
             // 파일 다운로드 및 저장
-            let file_path = cache_dir.join(if asset_name.ends_with(".md5") {"cvAutoTrack.md5"} else {asset_name});
+            let file_name = if asset_name.ends_with(".md5") {"cvAutoTrack.md5"} else {asset_name};
+            let file_path = cache_dir.join(file_name);
             debug!("file_path: {:?}", file_path);
-            let target_path = lib_path.clone().join(if asset_name.ends_with(".md5") {"cvAutoTrack.md5"} else {asset_name}); // ? cvAutoTrack instead of asset_name
 
-            // 비동기 다운로드로 변경
             download_file(
                 asset_url,
                 &file_path,
                 ws_handler.clone(),
                 update_info.clone(),
-                requester_id.clone(),
+                requester_id.to_string(),
             ).await?;
 
-            std::fs::create_dir_all(lib_path.clone())?;
-            std::fs::rename(file_path, target_path)?;
+            std::fs::rename(file_path, staging_path.join(file_name))?;
 
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
     }
 
-    update_info.done = true;
-    send_lib_update_info(ws_handler.clone(), requester_id.clone(), Some(update_info)).await?;
+    // 로드한 라이브러리는 검증 후 바로 해제해야 디렉토리를 옮길 수 있다.
+    let dll_path = staging_path.join("cvAutoTrack.dll");
+    let compile_version = tokio::task::spawn_blocking(move || validate_library(&dll_path)).await??;
+    log::debug!("받은 CVAT 검증 완료 (GetCompileVersion: {})", compile_version);
+    Ok(())
+}
+
+// 라이브러리를 해제한 직후에는 파일이 잠겨있을 수 있으므로, 몇 번 다시 시도한다.
+async fn rename_with_retry(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match fs::rename(from, to) {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt < 10 => {
+                attempt += 1;
+                log::debug!("{:?} -> {:?} 이동 실패, 다시 시도합니다: {}", from, to, e);
+                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// new_dir을 lib_path로 옮기고, 기존 lib_path는 backup_path에 보관한다.
+async fn swap_lib_dirs(new_dir: &Path, lib_path: &Path, backup_path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if lib_path.exists() {
+        if backup_path.exists() {
+            fs::remove_dir_all(backup_path)?;
+        }
+        rename_with_retry(lib_path, backup_path).await?;
+    }
+    if let Err(e) = rename_with_retry(new_dir, lib_path).await {
+        // 교체에 실패하면 기존 라이브러리를 되돌린다.
+        if backup_path.exists() && !lib_path.exists() {
+            fs::rename(backup_path, lib_path)?;
+        }
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+// 업데이트 전에 사용하던 라이브러리로 되돌린다. 되돌린 라이브러리는 다시 이전 버전으로 보관된다.
+pub async fn rollback_cvat(event_bus: &Arc<EventBus>, ws_handler: &Arc<WebSocketHandler>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let lib_path = path::get_lib_path();
+    let backup_path = path::get_lib_backup_path();
+    if !backup_path.join("cvAutoTrack.dll").exists() {
+        return Err("되돌릴 이전 버전의 CVAT가 없습니다.".into());
+    }
+
+    let was_loaded = get_app_state().get_instance().is_some();
+    let was_tracking = get_app_state().is_tracking();
+    log::debug!("CVAT 언로드 시도");
+    event_bus.emit(&AppEvent::Uninit()).await?;
+
+    let current_path = path::get_lib_restoring_path();
+    if current_path.exists() {
+        fs::remove_dir_all(&current_path)?;
+    }
+    if lib_path.exists() {
+        rename_with_retry(&lib_path, &current_path).await?;
+    }
+    swap_lib_dirs(&backup_path, &lib_path, &current_path).await?;
+    if current_path.exists() {
+        rename_with_retry(&current_path, &backup_path).await?;
+    }
+    log::debug!("CVAT를 이전 버전({})으로 되돌렸습니다.", get_local_version(&lib_path));
+    reload_cvat(event_bus, ws_handler, was_loaded, was_tracking).await
+}
 
+// 교체한 라이브러리를 다시 로드한다. 추적 중이었다면 추적도 다시 시작한다.
+async fn reload_cvat(
+    event_bus: &Arc<EventBus>,
+    ws_handler: &Arc<WebSocketHandler>,
+    was_loaded: bool,
+    was_tracking: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if was_tracking {
+        start_cvat(event_bus, ws_handler).await?;
+    } else if was_loaded {
+        let config = ConfigManager::global().get().await;
+        initialize_cvat(&config).map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    }
+    Ok(())
+}
+
+/*
+ * 라이브러리 디렉토리 교체는 rename 두 번으로 이루어지므로, 그 사이에 앱이 종료되면 라이브러리가 없을 수 있다.
+ * 시작할 때 남아있는 디렉토리로 복구한다.
+ * - 업데이트 중: cvAutoTrack -> cvAutoTrack.previous 이후 종료. 이전 버전을 되돌린다.
+ * - 되돌리는 중: cvAutoTrack -> cvAutoTrack.restoring 이후 종료. 옮겨둔 현재 버전을 되돌린다.
+ *   cvAutoTrack.previous -> cvAutoTrack 이후 종료. 옮겨둔 현재 버전을 이전 버전으로 보관한다.
+ */
+pub fn recover_lib_dirs() -> std::io::Result<()> {
+    restore_lib_dirs(&path::get_lib_path(), &path::get_lib_backup_path(), &path::get_lib_restoring_path())
+}
+
+pub(crate) fn restore_lib_dirs(lib_path: &Path, backup_path: &Path, restoring_path: &Path) -> std::io::Result<()> {
+    if !lib_path.exists() {
+        if restoring_path.exists() {
+            log::error!("라이브러리를 되돌리는 중 종료되었습니다. {:?}를 복구합니다.", restoring_path);
+            fs::rename(restoring_path, lib_path)?;
+        } else if backup_path.exists() {
+            log::error!("라이브러리를 교체하는 중 종료되었습니다. {:?}를 복구합니다.", backup_path);
+            fs::rename(backup_path, lib_path)?;
+        }
+    }
+    if restoring_path.exists() {
+        if backup_path.exists() {
+            fs::remove_dir_all(restoring_path)?;
+        } else {
+            fs::rename(restoring_path, backup_path)?;
+        }
+    }
     Ok(())
 }
 
@@ -338,7 +412,7 @@ fn parse_iso8601(date: &str) -> std::result::Result<std::time::SystemTime, Box<d
     Ok(datetime.into())
 }
 
-fn get_local_version(lib_path: &PathBuf) -> String {
+pub fn get_local_version(lib_path: &Path) -> String {
     // TODO:
     match std::fs::read_to_string(lib_path.join("version.tag")) {
         Ok(contents) => contents.trim().to_string(),
@@ -349,36 +423,123 @@ fn get_local_version(lib_path: &PathBuf) -> String {
     }
 }
 
+// version이 release_name보다 같거나 새 버전이라면 true
 pub fn compare_versions(version: &str, release_name: &str) -> bool {
     debug!("compare_versions({}, {})", version, release_name);
-    let version = version.trim_start_matches('v');
-    let release_name = release_name.trim_start_matches('v');
-    let latest_version = release_name.trim();
+    let version = version.trim().trim_start_matches('v');
+    let latest_version = release_name.trim().trim_start_matches('v');
     if version.eq(latest_version) {
         return true;
-    } else {
-        if version.len() > 0 && release_name.len() > 0 {
-            let current_semver = version.split('.').map(|s| s.parse::<i32>().unwrap()).collect::<Vec<i32>>();
-            let latest_semver = latest_version.split('.').map(|s| s.parse::<i32>().unwrap()).collect::<Vec<i32>>();
-            if current_semver[0] > latest_semver[0] {
-                return true;
-            } else if current_semver[0] == latest_semver[0] {
-                if current_semver[1] > latest_semver[1] {
-                    return true;
-                } else if current_semver[1] == latest_semver[1] {
-                    if current_semver[2] >= latest_semver[2] {
-                        return true;
-                    }
-                }
-            }
-        }
+    }
+    if version.is_empty() || latest_version.is_empty() {
         return false;
     }
-        
+    let (current_semver, current_pre) = parse_semver(version);
+    let (latest_semver, latest_pre) = parse_semver(latest_version);
+    match current_semver.cmp(&latest_semver) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        // 같은 번호라면 정식 버전이 프리릴리즈(1.2.0-beta.1)보다 새 버전이다.
+        std::cmp::Ordering::Equal => match (current_pre, latest_pre) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(current), Some(latest)) => compare_prerelease(current, latest).is_ge(),
+        },
+    }
+}
+
+/*
+ * semver의 프리릴리즈 비교. '.'으로 나눈 항목을 앞에서부터 비교한다.
+ * 숫자끼리는 크기로 (beta.10 > beta.9), 숫자와 문자는 숫자가 낮고, 문자끼리는 사전순으로 비교한다.
+ * 앞의 항목이 모두 같다면 항목이 많은 쪽이 새 버전이다. (beta.1 > beta)
+ */
+fn compare_prerelease(current: &str, latest: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let mut current = current.split('.');
+    let mut latest = latest.split('.');
+    loop {
+        let ordering = match (current.next(), latest.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+// "1.2.3-beta.1+build" -> ([1, 2, 3], Some("beta.1"))
+fn parse_semver(version: &str) -> ([u32; 3], Option<&str>) {
+    let version = version.split('+').next().unwrap_or(version);
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    let mut semver = [0; 3];
+    for (i, part) in core.split('.').take(3).enumerate() {
+        semver[i] = part.parse().unwrap_or(0);
+    }
+    (semver, pre)
 }
 
 use std::io::Write;
+use std::time::Duration;
+use reqwest::header::{HeaderMap, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+
+// 크기를 모르는 파일은 이만큼 받을 때마다 진행 상황을 보낸다.
+const UNKNOWN_SIZE_PROGRESS_STEP: u64 = 1024 * 1024;
+// 재시도 간격의 최대값 (초)
+const MAX_RETRY_DELAY: u64 = 30;
+
+// 다시 시도해도 성공할 수 없는 실패 (404 등)
+#[derive(Debug)]
+struct PermanentError(String);
+
+impl std::fmt::Display for PermanentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for PermanentError {}
 
+// 다운로드 연결 설정
+pub(crate) struct DownloadOptions {
+    pub connect_timeout: Duration,
+    // None이면 읽기 시간 제한을 두지 않는다.
+    pub read_timeout: Option<Duration>,
+    pub retries: u32,
+}
+
+impl From<&AppConfig> for DownloadOptions {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(config.download_connect_timeout.max(1) as u64),
+            // 0이면 읽기 시간 제한을 두지 않는다.
+            read_timeout: match config.download_read_timeout {
+                0 => None,
+                secs => Some(Duration::from_secs(secs as u64)),
+            },
+            retries: config.download_retries,
+        }
+    }
+}
+
+/*
+ * url의 파일을 path에 받는다.
+ * 받는 중인 파일은 <path>.part에 저장하며, 실패하면 Range 요청으로 이어받는다.
+ * 서버의 파일이 바뀌었다면 이어받지 않도록 ETag 또는 Last-Modified를 <path>.part.validator에 저장해 If-Range로 보낸다.
+ * 실패할 때마다 1, 2, 4... 초(최대 MAX_RETRY_DELAY) 기다린 뒤 download_retries번까지 다시 시도한다.
+ * 408, 429를 제외한 4xx 응답은 다시 시도하지 않는다.
+ */
 pub async fn download_file(
     url: &str,
     path: &PathBuf,
@@ -386,50 +547,226 @@ pub async fn download_file(
     mut update_info: UpdateInfo,
     requester_id: String,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // Reqwest setup
-    let client = StreamClient::new();
-    let res = client
-        .get(url)
-        .header("User-Agent", "reqwest")
-        .send()
-        .await
-        .or(Err(format!("Failed to GET from '{}'", &url)))?;
-    let total_size = res
-        .content_length()
-        .ok_or(format!("Failed to get content length from '{}'", &url))?;
-    update_info.file_size = total_size;
+    // local 릴리즈 소스의 파일은 복사한다.
+    if let Some(src) = reqwest::Url::parse(url).ok().filter(|u| u.scheme() == "file") {
+        let src = src.to_file_path().or(Err(format!("Invalid file url '{}'", &url)))?;
+        let size = fs::copy(&src, path).or(Err(format!("Failed to copy '{}'", &url)))?;
+        update_info.file_size = size;
+        update_info.downloaded = size;
+        update_info.percent = 100.0;
+        ws_handler.send_to(requester_id, SendEvent::from(WsEvent::UpdateInfo {
+            info: Some(update_info)
+        })).await?;
+        return Ok(());
+    }
 
-    // download chunks
-    let mut file = File::create(path).or(Err(format!(
-        "Failed to create file '{}'",
-        path.clone().to_str().unwrap()
+    let options = DownloadOptions::from(&ConfigManager::global().get().await);
+    download_with(url, path, &options, ws_handler, update_info, requester_id).await
+}
+
+// http(s) url의 파일을 options에 따라 받는다.
+pub(crate) async fn download_with(
+    url: &str,
+    path: &PathBuf,
+    options: &DownloadOptions,
+    ws_handler: WebSocketHandler,
+    update_info: UpdateInfo,
+    requester_id: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let client = StreamClient::builder()
+        .connect_timeout(options.connect_timeout)
+        .build()?;
+
+    let mut part_path = path.clone().into_os_string();
+    part_path.push(".part");
+    let part_path = PathBuf::from(part_path);
+    let mut validator_path = part_path.clone().into_os_string();
+    validator_path.push(".validator");
+    let validator_path = PathBuf::from(validator_path);
+
+    let mut progress = DownloadProgress {
+        ws_handler,
+        requester_id,
+        update_info,
+        old_percent: -1.0,
+        last_reported: 0,
+    };
+    let mut attempt: u32 = 0;
+    loop {
+        match download_part(&client, url, &part_path, &validator_path, options.read_timeout, &mut progress).await {
+            Ok(_) => break,
+            Err(e) if e.is::<PermanentError>() => return Err(e),
+            Err(e) if attempt < options.retries => {
+                attempt += 1;
+                let delay = min(1u64 << min(attempt - 1, 5), MAX_RETRY_DELAY);
+                log::error!("다운로드 실패 ({}/{}): {}", attempt, options.retries, e);
+                log::debug!("{}초 후 다시 시도합니다.", delay);
+                tokio::time::sleep(Duration::from_secs(delay)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    fs::rename(&part_path, path).or(Err(format!(
+        "Failed to move downloaded file to '{}'",
+        path.to_string_lossy()
     )))?;
-    let mut downloaded: u64 = 0;
+    let _ = fs::remove_file(&validator_path);
+    Ok(())
+}
+
+// 다운로드 진행 상황을 업데이트를 요청한 클라이언트에게 보낸다.
+struct DownloadProgress {
+    ws_handler: WebSocketHandler,
+    requester_id: String,
+    update_info: UpdateInfo,
+    old_percent: f64,
+    last_reported: u64,
+}
+
+impl DownloadProgress {
+    // 서버가 처음부터 다시 보낼 때 진행률을 되돌린다.
+    fn restart(&mut self) {
+        self.old_percent = -1.0;
+        self.last_reported = 0;
+    }
+
+    // total이 None이면 file_size와 percent는 0으로 보낸다.
+    async fn report(&mut self, downloaded: u64, total: Option<u64>) {
+        self.update_info.downloaded = downloaded;
+        match total {
+            Some(total) => {
+                let downloaded = min(downloaded, total);
+                let percent = f64::trunc((downloaded as f64 * 100.0 / total.max(1) as f64) * 10.0) / 10.0;
+                if percent - self.old_percent <= 1.0 {
+                    return;
+                }
+                self.old_percent = percent;
+                self.update_info.downloaded = downloaded;
+                self.update_info.file_size = total;
+                self.update_info.percent = percent;
+            }
+            None => {
+                if downloaded < self.last_reported + UNKNOWN_SIZE_PROGRESS_STEP {
+                    return;
+                }
+                self.update_info.file_size = 0;
+                self.update_info.percent = 0.0;
+            }
+        }
+        self.last_reported = downloaded;
+        // 요청한 클라이언트가 연결을 끊었더라도 다운로드는 계속한다.
+        if let Err(e) = self.ws_handler.send_to(self.requester_id.clone(), SendEvent::from(WsEvent::UpdateInfo {
+            info: Some(self.update_info.clone())
+        })).await {
+            log::debug!("진행 상황 전송 실패: {}", e);
+        }
+    }
+}
+
+// If-Range에 쓸 수 있는 값. 약한 ETag는 쓸 수 없으므로 Last-Modified를 사용한다.
+fn range_validator(headers: &HeaderMap) -> Option<String> {
+    headers.get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.starts_with("W/"))
+        .or_else(|| headers.get(LAST_MODIFIED).and_then(|v| v.to_str().ok()))
+        .map(|v| v.to_string())
+}
+
+// part_path에 이미 받은 부분이 있다면 그 다음부터 받는다.
+async fn download_part(
+    client: &StreamClient,
+    url: &str,
+    part_path: &Path,
+    validator_path: &Path,
+    read_timeout: Option<Duration>,
+    progress: &mut DownloadProgress,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut offset = fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+    let validator = fs::read_to_string(validator_path).ok();
+    if offset > 0 && validator.is_none() {
+        // 같은 파일인지 확인할 수 없으므로 처음부터 받는다.
+        log::debug!("이어받을 파일의 validator가 없어 처음부터 받습니다.");
+        fs::remove_file(part_path)?;
+        offset = 0;
+    }
+    let mut req = client.get(url).header("User-Agent", "reqwest");
+    if let (true, Some(validator)) = (offset > 0, validator) {
+        log::debug!("{} 바이트부터 이어받기 시도", offset);
+        req = req.header(RANGE, format!("bytes={}-", offset)).header(IF_RANGE, validator);
+    }
+    let res = req.send().await.map_err(|e| format!("Failed to GET from '{}': {}", &url, e))?;
+
+    // Content-Range: bytes <start>-<end>/<total>
+    let range_total = res.headers().get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    let (mut file, mut downloaded, total_size) = match res.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            let file = fs::OpenOptions::new().append(true).open(part_path)?;
+            let total = range_total.or(res.content_length().map(|len| offset + len));
+            (file, offset, total)
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE if range_total == Some(offset) => {
+            // 이전에 끝까지 받았지만 옮기지 못한 경우
+            return Ok(());
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            // 서버의 파일이 바뀌었으므로 처음부터 다시 받는다.
+            fs::remove_file(part_path)?;
+            return Err(format!("'{}'의 크기가 바뀌었습니다.", &url).into());
+        }
+        status if status.is_success() => {
+            // Range를 지원하지 않거나 파일이 바뀐 경우 서버는 처음부터 다시 보낸다.
+            let file = File::create(part_path).or(Err(format!(
+                "Failed to create file '{}'",
+                part_path.to_string_lossy()
+            )))?;
+            match range_validator(res.headers()) {
+                Some(validator) => fs::write(validator_path, validator)?,
+                None => { let _ = fs::remove_file(validator_path); }
+            }
+            progress.restart();
+            (file, 0, res.content_length())
+        }
+        status if status.is_client_error()
+            && status != StatusCode::REQUEST_TIMEOUT
+            && status != StatusCode::TOO_MANY_REQUESTS => {
+            return Err(PermanentError(format!("Failed to GET from '{}': {}", &url, status)).into());
+        }
+        status => return Err(format!("Failed to GET from '{}': {}", &url, status).into()),
+    };
+
+    // download chunks
     let mut stream = res.bytes_stream();
-    let mut old_percent: f64 = -1.0;
-    while let Some(item) = stream.next().await {
-        let chunk = item.or(Err("Error while downloading file".to_string()))?;
+    loop {
+        let item = match read_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, stream.next()).await
+                .or(Err(format!("Timed out while downloading '{}'", &url)))?,
+            None => stream.next().await,
+        };
+        let Some(item) = item else {
+            break;
+        };
+        let chunk = item.map_err(|e| format!("Error while downloading file: {}", e))?;
         file.write_all(&chunk)
             .or(Err("Error while writing to file".to_string()))?;
-        let new = min(downloaded + (chunk.len() as u64), total_size);
-        downloaded = new;
-        let mut update_info = update_info.clone();
-        let requester_id = requester_id.clone();
-        update_info.downloaded = downloaded;
-        let percent = f64::trunc((downloaded as f64 * 100.0 / total_size as f64) * 10.0) / 10.0;
-        if percent - old_percent > 1.0 {
-            old_percent = percent;
-            update_info.percent = percent;
-            ws_handler.send_to(requester_id.clone(), SendEvent::from(WsEvent::UpdateInfo { 
-                info: Some(update_info.clone())
-            })).await?;
-        }
+        downloaded += chunk.len() as u64;
+        progress.report(downloaded, total_size).await;
     }
+    file.flush()?;
 
+    if let Some(total) = total_size {
+        if downloaded < total {
+            return Err(format!("Connection closed at {}/{} bytes", downloaded, total).into());
+        }
+    }
     Ok(())
 }
 
-fn extract_files_from_zip(arch_path: &PathBuf, mappings: HashMap<&str, &PathBuf>) -> std::result::Result<(), Box<dyn Error + Send + Sync>> {
+fn extract_files_from_zip(arch_path: &PathBuf, mappings: HashMap<&str, &Path>) -> std::result::Result<(), Box<dyn Error + Send + Sync>> {
     let file = fs::File::open(arch_path).unwrap();
 
     let mut archive = zip::ZipArchive::new(file).unwrap();
@@ -484,14 +821,9 @@ pub async fn register_events(
         let ws_handler = ws_handler_app.clone();
         let config = config_clone.clone();
         async move {
-            let force = if let Some(data) = &params.data {
-                match data {
-                    RequestDataTypes::CheckAppUpdate(data) => data.force,
-                    _ => false
-                }
-            } else {
-                false
-            };
+            let force = params.data.as_ref()
+                .and_then(|data| data.update_check())
+                .is_some_and(|data| data.force);
             check_app_update(&config, id, (*ws_handler).clone(), force).await
         }
     }).await?;
@@ -503,15 +835,21 @@ pub async fn register_events(
         let ws_handler = ws_handler_lib.clone();
         let config = config_clone.clone();
         async move {
-            let force = if let Some(data) = &params.data {
-                match data {
-                    RequestDataTypes::CheckLibUpdate(data) => data.force,
-                    _ => false
-                }
-            } else {
-                false
-            };
-            check_lib_update(&config, id, &event_bus.clone(), (*ws_handler).clone(), force).await
+            let force = params.data.as_ref()
+                .and_then(|data| data.update_check())
+                .is_some_and(|data| data.force);
+            check_lib_update(&config, id, &event_bus, &ws_handler, force).await
+        }
+    }).await?;
+    let event_bus_rollback = event_bus.clone();
+    let ws_handler_rollback = ws_handler.clone();
+    ws_handler.register("rollbackLib", move |id, _| {
+        let event_bus = event_bus_rollback.clone();
+        let ws_handler = ws_handler_rollback.clone();
+        async move {
+            rollback_cvat(&event_bus, &ws_handler).await?;
+            // 되돌린 라이브러리의 버전을 알린다.
+            send_lib_update_info((*ws_handler).clone(), id, None).await
         }
     }).await?;
 
@@ -556,11 +894,11 @@ pub async fn check_lib_update(
     config: &AppConfig, 
     client_id: String,
     event_bus: &Arc<EventBus>,
-    ws_handler: WebSocketHandler, 
+    ws_handler: &Arc<WebSocketHandler>,
     force: bool
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     if config.auto_lib_update {
-        match download_cvat(&event_bus.clone(), ws_handler.clone(), client_id.clone(), force).await {
+        match download_cvat(event_bus, ws_handler, client_id.clone(), force).await {
             Ok(_) => {
                 log::debug!("Lib Ready!");
                 Ok(())
@@ -568,14 +906,14 @@ pub async fn check_lib_update(
             Err(e) => {
                 log::error!("{}", e);
                 log::debug!("현재 버전을 계속 사용합니다!");
-                send_lib_update_info(ws_handler.clone(), client_id.clone(), None).await?;
+                send_lib_update_info((**ws_handler).clone(), client_id.clone(), None).await?;
                 Err(e)
             }
         }
     } else {
         log::debug!("자동 업데이트가 꺼져있습니다.");
         log::debug!("현재 버전을 계속 사용합니다!");
-        send_lib_update_info(ws_handler.clone(), client_id.clone(), None).await?;
+        send_lib_update_info((**ws_handler).clone(), client_id.clone(), None).await?;
         Ok(())
     }
 }
@@ -609,28 +947,24 @@ pub async fn send_lib_update_info(
     requester_id: String,
     update_info: Option<UpdateInfo>
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let info: UpdateInfo;
-    let lib_path: PathBuf;
-    let version_string: String;
-    if update_info.is_none() {
-        log::debug!("update_info is none");
-        lib_path = path::get_lib_path();
-        version_string = get_local_version(&lib_path);
-
-        info = update_info.unwrap_or(UpdateInfo {
-            target_type: "cvat".to_string(),
-            current_version: version_string,
-            target_version: String::from(""),
-            display_version_name: String::from(""),
-            downloaded: 0,
-            file_size: 0,
-            percent: 0.0,
-            done: true,
-            updated: false
-        });
-    } else {
-        info = update_info.unwrap();
-    }
+    let info = match update_info {
+        Some(info) => info,
+        None => {
+            log::debug!("update_info is none");
+            let lib_path = path::get_lib_path();
+            UpdateInfo {
+                target_type: "cvat".to_string(),
+                current_version: get_local_version(&lib_path),
+                target_version: String::from(""),
+                display_version_name: String::from(""),
+                downloaded: 0,
+                file_size: 0,
+                percent: 0.0,
+                done: true,
+                updated: false
+            }
+        }
+    };
     
     ws_handler.send_to(requester_id.clone(), SendEvent::from(WsEvent::UpdateInfo { 
         info: Some(info)
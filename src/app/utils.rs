@@ -1,7 +1,10 @@
-use crate::app::path;
+use crate::app::{logger, path};
 use std::fs;
+#[cfg(windows)]
 use std::ffi::CString;
+#[cfg(windows)]
 use std::path::Path;
+#[cfg(windows)]
 use windows::{
     core::{s, Result as WinResult, PCSTR}, Win32::Foundation::*, Win32::Security::*, Win32::{System::Memory::*, UI::Shell::ShellExecuteA},
     Win32::System::Threading::*
@@ -28,13 +31,10 @@ pub fn check_proj_directory() -> Result<bool, std::io::Error> {
     let cwd = path::get_current_work_directory();
 
     // 현재 작업 디렉토리와 프로젝트 디렉토리를 대조한다.
-    if !cwd.eq(&target_dir) {
-        return Ok(false);
-    } else {
-        return Ok(true);
-    }
+    Ok(cwd.eq(&target_dir))
 }
 
+#[cfg(windows)]
 pub fn check_elevation(target: &Path, args: Vec<String>) -> bool {
     if let Ok(elevated) = is_elevated() {
         if elevated {
@@ -45,6 +45,7 @@ pub fn check_elevation(target: &Path, args: Vec<String>) -> bool {
     false
 }
 
+#[cfg(windows)]
 pub fn run_shell_execute(target: &Path, args: Vec<String>, with_elevation: Option<bool>) {
     let path_string = CString::new(target.to_str().unwrap()).unwrap();
     let path_ptr = CString::as_bytes_with_nul(&path_string);
@@ -69,6 +70,7 @@ pub fn run_shell_execute(target: &Path, args: Vec<String>, with_elevation: Optio
     }
 }
 
+#[cfg(windows)]
 pub fn is_elevated() -> WinResult<bool> {
     unsafe {
         let mut token = HANDLE::default();
@@ -122,6 +124,14 @@ pub fn is_process_already_running() -> bool {
 pub fn terminate_process() {
     std::process::exit(0);
 }
+
+// 트레이 아이콘이 없는 플랫폼에서는 프로세스가 종료될 때까지 메인 스레드를 붙잡아 둔다.
+#[cfg(not(windows))]
+pub fn wait_for_exit() {
+    loop {
+        std::thread::park();
+    }
+}
 use log4rs::{
     append::{
         console::{ConsoleAppender, Target},
@@ -239,10 +249,9 @@ pub fn enable_debug() -> Result<(), log::SetLoggerError> {
             }
         }
 
-        let _handle;
-        match log4rs::init_config(config) {
-            Ok(handle) => {
-                _handle = handle;
+        match logger::init() {
+            Ok(_) => {
+                logger::set_file_logger(log4rs::Logger::new(config));
                 Ok(())
             }
             Err(e) => {
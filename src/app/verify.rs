@@ -0,0 +1,224 @@
+use crate::app::release::{Release, ReleaseAsset};
+use crate::models::AppConfig;
+use ed25519_dalek::{Signature, VerifyingKey};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/*
+ * 업데이트 서명 검증에 사용할 ed25519 공개키 (32바이트 hex).
+ * 빌드할 때 GPA_UPDATE_PUBLIC_KEY 환경 변수로 넣는다. (예: GPA_UPDATE_PUBLIC_KEY=<hex> cargo build --release)
+ * 릴리즈에는 첨부 파일마다 <파일명>.sig로 64바이트 ed25519 서명을 그대로 또는 hex로 올린다.
+ */
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("GPA_UPDATE_PUBLIC_KEY");
+
+/*
+ * 서명 검증 방법.
+ * 서명 검증은 선택 사항이다. required가 아니라면 공개키와 .sig가 모두 있을 때만 검증한다.
+ * required라면 공개키나 .sig가 없는 업데이트를 거부한다.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SignaturePolicy<'a> {
+    pub public_key: Option<&'a str>,
+    pub required: bool,
+}
+
+impl SignaturePolicy<'static> {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            public_key: UPDATE_PUBLIC_KEY,
+            required: config.require_update_signature,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    Sha256(String),
+    Md5(String),
+}
+
+impl Checksum {
+    // "sha256:<hex>" 형식 (GitHub releases API의 digest)
+    fn from_digest(digest: &str) -> Option<Self> {
+        digest.strip_prefix("sha256:").and_then(|hex| parse_hex(hex, 64)).map(Checksum::Sha256)
+    }
+
+    // sha256sum/md5sum 출력 형식 ("<hex>  <파일명>") 또는 hex만 있는 파일
+    pub(crate) fn from_sidecar(contents: &[u8], sha256: bool) -> Option<Self> {
+        let contents = String::from_utf8_lossy(contents);
+        let hex = contents.split_whitespace().next()?;
+        if sha256 {
+            parse_hex(hex, 64).map(Checksum::Sha256)
+        } else {
+            parse_hex(hex, 32).map(Checksum::Md5)
+        }
+    }
+}
+
+pub(crate) fn parse_hex(hex: &str, len: usize) -> Option<String> {
+    let hex = hex.trim().to_ascii_lowercase();
+    (hex.len() == len && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some(hex)
+}
+
+/*
+ * 다운로드한 첨부 파일을 검증한다. 실패하면 파일을 지우고 에러를 반환한다.
+ * 해시는 반드시 있어야 하며 SHA-256을 우선한다.
+ * (digest 필드 -> <파일명>.sha256 첨부 파일 -> allow_md5인 경우 <파일명>.md5 첨부 파일,
+ *  없다면 릴리즈에 하나뿐인 .md5 첨부 파일)
+ * 서명은 signature에 따라 검증한다.
+ */
+pub async fn verify_asset(
+    release: &Release,
+    asset: &ReleaseAsset,
+    path: &PathBuf,
+    allow_md5: bool,
+    signature: &SignaturePolicy<'_>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let result = verify(release, asset, path, allow_md5, signature).await;
+    if let Err(e) = &result {
+        log::error!("{} 검증 실패: {}", asset.name, e);
+        if let Err(e) = std::fs::remove_file(path) {
+            log::debug!("Failed to remove unverified file: {}", e);
+        }
+    }
+    result
+}
+
+async fn verify(
+    release: &Release,
+    asset: &ReleaseAsset,
+    path: &PathBuf,
+    allow_md5: bool,
+    policy: &SignaturePolicy<'_>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let checksum = expected_checksum(release, asset, allow_md5).await?
+        .ok_or(format!("{}의 해시 정보가 릴리즈에 없습니다.", asset.name))?;
+    let actual = file_checksum(path, &checksum)?;
+    if actual != checksum {
+        return Err(format!("해시가 일치하지 않습니다. (예상: {:?}, 실제: {:?})", checksum, actual).into());
+    }
+    log::debug!("{} 해시 확인: {:?}", asset.name, checksum);
+
+    match find_asset(release, &format!("{}.sig", asset.name)) {
+        Some(sig) => {
+            let signature = fetch_bytes(&sig.browser_download_url).await?;
+            match policy.public_key {
+                Some(key) => verify_signature(path, &signature, key)?,
+                None if policy.required => return Err("서명을 검증할 공개키가 내장되어 있지 않습니다.".into()),
+                None => log::debug!("공개키가 내장되어 있지 않아 서명 검증을 건너뜁니다."),
+            }
+        }
+        None if policy.required => return Err(format!("{}의 서명이 릴리즈에 없습니다.", asset.name).into()),
+        None => {}
+    }
+    Ok(())
+}
+
+async fn expected_checksum(
+    release: &Release,
+    asset: &ReleaseAsset,
+    allow_md5: bool,
+) -> Result<Option<Checksum>, Box<dyn Error + Send + Sync>> {
+    if let Some(checksum) = asset.digest.as_deref().and_then(Checksum::from_digest) {
+        return Ok(Some(checksum));
+    }
+    let sidecar = match find_asset(release, &format!("{}.sha256", asset.name)) {
+        Some(sidecar) => Some((sidecar, true)),
+        None if allow_md5 => find_md5(release, asset).map(|sidecar| (sidecar, false)),
+        None => None,
+    };
+    match sidecar {
+        Some((sidecar, sha256)) => {
+            let contents = fetch_bytes(&sidecar.browser_download_url).await?;
+            Checksum::from_sidecar(&contents, sha256)
+                .map(Some)
+                .ok_or(format!("{}의 형식이 올바르지 않습니다.", sidecar.name).into())
+        }
+        None => Ok(None),
+    }
+}
+
+// 다른 첨부 파일의 해시나 서명 파일인지 확인한다. 이런 파일은 verify_asset에서만 받는다.
+pub fn is_sidecar(release: &Release, asset: &ReleaseAsset) -> bool {
+    let name = asset.name.to_ascii_lowercase();
+    [".sha256", ".md5", ".sig"].iter()
+        .filter_map(|ext| name.strip_suffix(ext))
+        .any(|target| find_asset(release, target).is_some())
+}
+
+// <파일명>.md5가 없다면, 라이브러리 미러처럼 다른 이름으로 올라온 .md5가 하나뿐일 때 그 파일을 쓴다.
+// .md5가 여러 개라면 어느 파일의 해시인지 알 수 없으므로 쓰지 않는다.
+pub(crate) fn find_md5<'a>(release: &'a Release, asset: &ReleaseAsset) -> Option<&'a ReleaseAsset> {
+    find_asset(release, &format!("{}.md5", asset.name)).or_else(|| {
+        let mut md5 = release.assets.iter().filter(|a| a.name.to_ascii_lowercase().ends_with(".md5"));
+        match (md5.next(), md5.next()) {
+            (Some(only), None) => {
+                log::debug!("{}의 해시로 {}을 사용합니다.", asset.name, only.name);
+                Some(only)
+            }
+            _ => None,
+        }
+    })
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Option<&'a ReleaseAsset> {
+    release.assets.iter().find(|a| a.name.eq_ignore_ascii_case(name))
+}
+
+// 해시, 서명 같은 작은 첨부 파일을 읽어온다.
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    if let Some(src) = reqwest::Url::parse(url).ok().filter(|u| u.scheme() == "file") {
+        let src = src.to_file_path().or(Err(format!("Invalid file url '{}'", &url)))?;
+        return Ok(std::fs::read(src)?);
+    }
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "reqwest")
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+fn file_checksum(path: &PathBuf, expected: &Checksum) -> Result<Checksum, Box<dyn Error + Send + Sync>> {
+    match expected {
+        Checksum::Sha256(_) => Ok(Checksum::Sha256(hash_file::<Sha256>(path)?)),
+        Checksum::Md5(_) => Ok(Checksum::Md5(hash_file::<Md5>(path)?)),
+    }
+}
+
+fn hash_file<D: Digest>(path: &PathBuf) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+// 서명 파일은 64바이트 그대로이거나 hex 문자열이다.
+pub(crate) fn verify_signature(path: &Path, signature: &[u8], public_key: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let key: [u8; 32] = hex::decode(public_key.trim())?
+        .try_into()
+        .or(Err("내장된 공개키가 올바르지 않습니다."))?;
+    let key = VerifyingKey::from_bytes(&key)?;
+    let signature: [u8; 64] = match signature.len() {
+        64 => signature.try_into()?,
+        _ => hex::decode(String::from_utf8_lossy(signature).trim())?
+            .try_into()
+            .or(Err("서명 파일이 올바르지 않습니다."))?,
+    };
+    let data = std::fs::read(path)?;
+    key.verify_strict(&data, &Signature::from_bytes(&signature))
+        .or(Err("서명이 일치하지 않습니다."))?;
+    log::debug!("{:?} 서명 확인", path);
+    Ok(())
+}
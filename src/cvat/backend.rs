@@ -0,0 +1,110 @@
+use super::bindings::cvAutoTrack;
+use super::error::*;
+use super::mock::MockBackend;
+use super::replay::ReplayBackend;
+use crate::app::path::{get_app_path, get_lib_path};
+use crate::models::{AppConfig, TrackingBackendKind};
+use libc::{c_double, c_int};
+use std::ffi::CStr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/*
+ * Tracker가 위치 정보를 얻어오는 대상.
+ * cvAutoTrack.dll 외의 구현체(mock, replay 등)로도 추적 루프, 에러 번역, broadcast 경로를
+ * 그대로 사용할 수 있도록 Tracker는 이 trait에만 의존한다.
+ */
+pub trait TrackingBackend: Send + Sync {
+    fn init(&self) -> bool;
+    fn uninit(&self) -> bool;
+    fn poll_position(&self, x: &mut c_double, y: &mut c_double, a: &mut c_double, m: &mut c_int) -> bool;
+    fn poll_rotation(&self, r: &mut c_double) -> bool;
+    // 번역되지 않은 원본 에러 JSON (`{"errorList": [...]}`)
+    fn last_error(&self) -> String;
+    fn version(&self) -> String;
+    // 다음 poll까지 기다릴 시간. None이면 capture_interval(에러 시 capture_delay_on_error)을 따른다.
+    fn next_delay(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// 설정된 종류에 맞는 백엔드를 생성한다.
+pub fn create_backend(config: &AppConfig) -> Result<Arc<dyn TrackingBackend>> {
+    log::debug!("Create Tracking Backend: {:?}", config.tracking_backend);
+    match config.tracking_backend {
+        TrackingBackendKind::Cvat => {
+            let cvat = unsafe {
+                cvAutoTrack::new(get_lib_path().join("cvAutoTrack.dll").to_str().unwrap())
+            }.map_err(|e| CvatError::Library(e.to_string()))?;
+            Ok(Arc::new(cvat))
+        }
+        TrackingBackendKind::Mock => Ok(Arc::new(MockBackend::new())),
+        TrackingBackendKind::Replay => {
+            if config.replay_file.is_empty() {
+                return Err(CvatError::Initialization("replay_file이 설정되지 않았습니다.".to_string()));
+            }
+            let replay_path = get_app_path().join(&config.replay_file);
+            let speed = config.replay_speed as f64 / 100.0;
+            Ok(Arc::new(ReplayBackend::from_file(&replay_path, speed, config.replay_loop)?))
+        }
+    }
+}
+
+/*
+ * 업데이트로 받은 라이브러리를 실제로 로드해 GetCompileVersion을 호출해본다. 성공하면 그 버전을 반환한다.
+ * 의존하는 DLL은 현재 라이브러리 디렉토리가 아니라 dll_path의 디렉토리에서 찾도록 한다.
+ */
+pub fn validate_library(dll_path: &Path) -> Result<String> {
+    #[cfg(windows)]
+    let library = unsafe {
+        use libloading::os::windows::{Library, LOAD_WITH_ALTERED_SEARCH_PATH};
+        Library::load_with_flags(dll_path, LOAD_WITH_ALTERED_SEARCH_PATH).map(libloading::Library::from)
+    };
+    #[cfg(not(windows))]
+    let library = unsafe { libloading::Library::new(dll_path) };
+    let cvat = library
+        .and_then(|library| unsafe { cvAutoTrack::from_library(library) })
+        .map_err(|e| CvatError::Library(e.to_string()))?;
+    if let Err(e) = &cvat.GetCompileVersion {
+        return Err(CvatError::Library(format!("GetCompileVersion을 찾을 수 없습니다: {}", e)));
+    }
+    let version = TrackingBackend::version(&cvat);
+    if version.is_empty() {
+        return Err(CvatError::Library("GetCompileVersion이 빈 버전을 반환했습니다.".to_string()));
+    }
+    Ok(version)
+}
+
+impl TrackingBackend for cvAutoTrack {
+    fn init(&self) -> bool {
+        // 구버전 DLL에는 심볼이 없을 수 있으므로, 없으면 호출하지 않는다.
+        self.init.is_ok() && unsafe { cvAutoTrack::init(self) }
+    }
+
+    fn uninit(&self) -> bool {
+        self.uninit.is_ok() && unsafe { cvAutoTrack::uninit(self) }
+    }
+
+    fn poll_position(&self, x: &mut c_double, y: &mut c_double, a: &mut c_double, m: &mut c_int) -> bool {
+        unsafe { self.GetTransformOfMap(x, y, a, m) }
+    }
+
+    fn poll_rotation(&self, r: &mut c_double) -> bool {
+        unsafe { self.GetRotation(r) }
+    }
+
+    fn last_error(&self) -> String {
+        let mut cs: [i8; 256] = [0; 256];
+        let c_buf: *mut i8 = cs.as_mut_ptr();
+        unsafe { self.GetLastErrJson(c_buf, 256) };
+        unsafe { CStr::from_ptr(c_buf) }.to_str().unwrap_or("{}").to_string()
+    }
+
+    fn version(&self) -> String {
+        let mut c_buf: [i8; 256] = [0; 256];
+        unsafe { self.GetCompileVersion(c_buf.as_mut_ptr(), 256); }
+        let c_str: &CStr = unsafe { CStr::from_ptr(c_buf.as_ptr()) };
+        c_str.to_str().unwrap_or("").to_string()
+    }
+}
@@ -489,7 +489,4 @@ impl cvAutoTrack {
             .as_ref()
             .expect("Expected function, got error."))()
     }
-    pub fn close(&self) {
-        drop(&self.__library);
-    }
 }
\ No newline at end of file
@@ -0,0 +1,115 @@
+use crate::models::{AppConfig, TrackData, TrackDelta, WsEvent};
+use libc::c_double;
+use std::time::{Duration, Instant};
+
+// DeltaEncoder의 전송 조건. 정수로 저장된 임계값과 keepalive(ms)를 비교에 쓸 타입으로 바꿔둔다.
+#[derive(Debug, Clone)]
+pub struct DeltaConfig {
+    pub change_only: bool,
+    pub min_distance: c_double,
+    pub min_angle: c_double,
+    pub keepalive: Duration,
+    pub delta_payload: bool,
+}
+
+impl From<&AppConfig> for DeltaConfig {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            change_only: config.track_change_only,
+            min_distance: config.track_min_distance as c_double,
+            min_angle: config.track_min_angle as c_double,
+            keepalive: Duration::from_millis(config.track_keepalive.into()),
+            delta_payload: config.track_delta_payload,
+        }
+    }
+}
+
+/*
+ * 추적 결과 중 클라이언트에게 보낼 것만 골라 WsEvent로 만든다.
+ * change_only가 꺼져있으면 매번 전체 track 이벤트를 보낸다.
+ * 켜져있으면 임계값 이상 바뀐 경우에만 보내되, keepalive 주기마다 전체 track 이벤트를 보낸다.
+ * delta_payload가 켜져있으면 바뀐 항목만 담은 trackDelta 이벤트를 보낸다.
+ */
+pub struct DeltaEncoder {
+    // 클라이언트가 알고 있는 마지막 값
+    last_sent: Option<TrackData>,
+    last_full_at: Option<Instant>,
+}
+
+impl DeltaEncoder {
+    pub fn new() -> Self {
+        Self {
+            last_sent: None,
+            last_full_at: None,
+        }
+    }
+
+    pub fn next(&mut self, config: &DeltaConfig, data: &TrackData, now: Instant) -> Option<WsEvent> {
+        let keepalive_due = self.last_full_at.is_none_or(|at| now.duration_since(at) >= config.keepalive);
+        let delta = match &self.last_sent {
+            Some(last) if config.change_only && !keepalive_due => DeltaEncoder::diff(config, last, data),
+            _ => return Some(self.full(data, now)),
+        };
+
+        if delta == TrackDelta::default() {
+            return None;
+        }
+        if !config.delta_payload {
+            return Some(self.full(data, now));
+        }
+
+        // 보낸 항목만 갱신해서, 작은 변화가 누적되면 다음 번에 보내지도록 한다.
+        if let Some(last) = self.last_sent.as_mut() {
+            if let (Some(x), Some(y)) = (delta.x, delta.y) {
+                last.x = x;
+                last.y = y;
+            }
+            if let Some(a) = delta.a {
+                last.a = a;
+            }
+            if let Some(r) = delta.r {
+                last.r = r;
+            }
+            if let Some(m) = delta.m {
+                last.m = m;
+            }
+            if let Some(err) = &delta.err {
+                last.err = err.clone();
+            }
+        }
+        Some(WsEvent::TrackDelta { data: delta })
+    }
+
+    fn diff(config: &DeltaConfig, last: &TrackData, data: &TrackData) -> TrackDelta {
+        let mut delta = TrackDelta::default();
+        let distance = ((data.x - last.x).powi(2) + (data.y - last.y).powi(2)).sqrt();
+        if distance > 0.0 && distance >= config.min_distance {
+            delta.x = Some(data.x);
+            delta.y = Some(data.y);
+        }
+        if changed_angle(data.a, last.a, config.min_angle) {
+            delta.a = Some(data.a);
+        }
+        if changed_angle(data.r, last.r, config.min_angle) {
+            delta.r = Some(data.r);
+        }
+        if data.m != last.m {
+            delta.m = Some(data.m);
+        }
+        if data.err != last.err {
+            delta.err = Some(data.err.clone());
+        }
+        delta
+    }
+
+    fn full(&mut self, data: &TrackData, now: Instant) -> WsEvent {
+        self.last_sent = Some(data.clone());
+        self.last_full_at = Some(now);
+        WsEvent::Track { data: data.clone() }
+    }
+}
+
+fn changed_angle(a: c_double, b: c_double, min_angle: c_double) -> bool {
+    let distance = ((a - b + 540.0).rem_euclid(360.0) - 180.0).abs();
+    distance > 0.0 && distance >= min_angle
+}
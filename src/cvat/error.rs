@@ -3,19 +3,19 @@ use std::fmt;
 
 #[derive(Debug)]
 pub enum CvatError {
-    InitializationError(String),
-    TrackingError(String),
-    LibraryError(String),
-    LockError(String),
+    Initialization(String),
+    Tracking(String),
+    Library(String),
+    Lock(String),
 }
 
 impl fmt::Display for CvatError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::InitializationError(msg) => write!(f, "Initialization error: {}", msg),
-            Self::TrackingError(msg) => msg.fmt(f),
-            Self::LibraryError(msg) => write!(f, "Library error: {}", msg),
-            Self::LockError(msg) => write!(f, "Lock error: {}", msg),
+            Self::Initialization(msg) => write!(f, "Initialization error: {}", msg),
+            Self::Tracking(msg) => msg.fmt(f),
+            Self::Library(msg) => write!(f, "Library error: {}", msg),
+            Self::Lock(msg) => write!(f, "Lock error: {}", msg),
         }
     }
 }
@@ -26,13 +26,13 @@ impl Error for CvatError {}
 impl From<Box<dyn Error + Send + Sync>> for CvatError {
     fn from(error: Box<dyn Error + Send + Sync>) -> Self {
         if error.is::<std::io::Error>() {
-            CvatError::InitializationError(error.to_string())
+            CvatError::Initialization(error.to_string())
         } else if error.to_string().contains("track") {
-            CvatError::TrackingError(error.to_string())
+            CvatError::Tracking(error.to_string())
         } else if error.to_string().contains("lock") {
-            CvatError::LockError(error.to_string())
+            CvatError::Lock(error.to_string())
         } else {
-            CvatError::LibraryError(error.to_string())
+            CvatError::Library(error.to_string())
         }
     }
 }
@@ -5,14 +5,10 @@ use std::sync::Arc;
 
 pub fn start_track_thread(_event_bus: Arc<EventBus>, ws_handler: Arc<WebSocketHandler>) -> bool {
     log::debug!("Start Track Thread");
-    if super::initialize_cvat().is_err() {
-        return false;
-    }
     let state = get_app_state();
-    let instance = state.get_instance();
-    if let Some(cvat) = instance.as_ref() {
+    if let Some(backend) = state.get_instance() {
         log::debug!("Cvat Instance Found");
-        let tracker = Tracker::new(cvat);
+        let tracker = Tracker::new(backend);
         match tracker.start(ws_handler) {
             Ok(_) => {
                 log::debug!("Track Thread Ready");
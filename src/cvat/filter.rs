@@ -0,0 +1,136 @@
+use crate::models::{AppConfig, RawTrackData, TrackData};
+use libc::{c_double, c_int};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+// TrackFilter의 설정. filter_smoothing(%)은 새 샘플의 가중치로, map별 속도 제한은 map id를 키로 바꿔둔다.
+#[derive(Debug, Clone)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    // 새 샘플의 가중치 (0 < alpha <= 1). 1이면 스무딩하지 않는다.
+    pub alpha: c_double,
+    // 초당 최대 이동 거리. 0이면 제한하지 않는다.
+    pub max_speed: c_double,
+    pub max_speed_by_map: BTreeMap<c_int, c_double>,
+    pub reject_limit: u32,
+}
+
+impl FilterConfig {
+    pub fn max_speed(&self, map_id: c_int) -> c_double {
+        *self.max_speed_by_map.get(&map_id).unwrap_or(&self.max_speed)
+    }
+}
+
+impl From<&AppConfig> for FilterConfig {
+    fn from(config: &AppConfig) -> Self {
+        let smoothing = config.filter_smoothing.min(95) as c_double / 100.0;
+        Self {
+            enabled: config.filter_enabled,
+            alpha: 1.0 - smoothing,
+            max_speed: config.filter_max_speed as c_double,
+            max_speed_by_map: config.filter_max_speed_by_map
+                .iter()
+                .filter_map(|(m, speed)| m.parse::<c_int>().ok().map(|m| (m, *speed as c_double)))
+                .collect(),
+            reject_limit: config.filter_reject_limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FilterState {
+    m: c_int,
+    x: c_double,
+    y: c_double,
+    a: c_double,
+    r: c_double,
+    at: Instant,
+}
+
+/*
+ * Tracker::track의 결과를 broadcast 하기 전에 거치는 필터.
+ * 1. 같은 지도 안에서 직전 위치로부터 최대 이동 속도를 넘는 샘플은 미니맵 오인식으로 보고 버린다.
+ *    단, reject_limit 보다 많이 연속으로 버려지면 실제 순간이동으로 보고 받아들인다.
+ * 2. 받아들인 샘플은 지수 이동 평균으로 스무딩한다.
+ * 필터를 거친 값은 x, y, a, r에, 원본 값은 raw에 담긴다.
+ */
+pub struct TrackFilter {
+    last: Option<FilterState>,
+    rejected: u32,
+}
+
+impl TrackFilter {
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            rejected: 0,
+        }
+    }
+
+    pub fn apply(&mut self, config: &FilterConfig, data: &mut TrackData, now: Instant) {
+        if !config.enabled || !data.err.is_empty() {
+            return;
+        }
+        let raw = RawTrackData { x: data.x, y: data.y, a: data.a, r: data.r };
+        data.raw = Some(raw);
+
+        let last = match self.last {
+            Some(last) if last.m == data.m => last,
+            _ => {
+                // 첫 샘플이거나 지도가 바뀐 경우, 비교 대상이 없으므로 그대로 사용한다.
+                self.accept(data, now);
+                return;
+            }
+        };
+
+        let max_speed = config.max_speed(data.m);
+        if max_speed > 0.0 {
+            let elapsed = now.duration_since(last.at).as_secs_f64();
+            let distance = ((raw.x - last.x).powi(2) + (raw.y - last.y).powi(2)).sqrt();
+            if distance > max_speed * elapsed {
+                self.rejected += 1;
+                if self.rejected <= config.reject_limit {
+                    log::debug!("Filter: 튀는 위치 무시 ({:.1} > {:.1})", distance, max_speed * elapsed);
+                    data.x = last.x;
+                    data.y = last.y;
+                    data.a = last.a;
+                    data.r = last.r;
+                    return;
+                }
+                self.accept(data, now);
+                return;
+            }
+        }
+
+        let alpha = config.alpha;
+        data.x = last.x + alpha * (raw.x - last.x);
+        data.y = last.y + alpha * (raw.y - last.y);
+        data.a = smooth_angle(last.a, raw.a, alpha);
+        data.r = smooth_angle(last.r, raw.r, alpha);
+        self.accept(data, now);
+    }
+
+    fn accept(&mut self, data: &TrackData, now: Instant) {
+        self.rejected = 0;
+        self.last = Some(FilterState {
+            m: data.m,
+            x: data.x,
+            y: data.y,
+            a: data.a,
+            r: data.r,
+            at: now,
+        });
+    }
+}
+
+// 각도(도)는 360도 경계를 넘어갈 수 있으므로 가까운 방향으로 보간한다.
+// 결과는 입력과 같은 범위로 돌려준다. 음수 입력이 있다면 -180..180, 아니라면 0..360 범위다.
+fn smooth_angle(last: c_double, new: c_double, alpha: c_double) -> c_double {
+    let delta = (new - last + 540.0).rem_euclid(360.0) - 180.0;
+    let smoothed = new - (1.0 - alpha) * delta;
+    if last < 0.0 || new < 0.0 {
+        (smoothed + 180.0).rem_euclid(360.0) - 180.0
+    } else {
+        smoothed.rem_euclid(360.0)
+    }
+}
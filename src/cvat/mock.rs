@@ -0,0 +1,64 @@
+use super::backend::TrackingBackend;
+use libc::{c_double, c_int};
+use std::time::Instant;
+
+/*
+ * cvAutoTrack.dll 없이 동작하는 가상 백엔드.
+ * 게임 없이(또는 Windows가 아닌 환경에서) 지도/오버레이를 개발할 수 있도록
+ * 몬드성 부근을 일정한 속도로 도는 위치를 만들어낸다.
+ */
+pub struct MockBackend {
+    started_at: Instant,
+}
+
+const CENTER_X: c_double = -2000.0;
+const CENTER_Y: c_double = -5000.0;
+const RADIUS: c_double = 300.0;
+// 한 바퀴 도는데 걸리는 시간 (초)
+const PERIOD: c_double = 60.0;
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+        }
+    }
+
+    fn phase(&self) -> c_double {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        (elapsed / PERIOD) * std::f64::consts::TAU
+    }
+}
+
+impl TrackingBackend for MockBackend {
+    fn init(&self) -> bool {
+        true
+    }
+
+    fn uninit(&self) -> bool {
+        true
+    }
+
+    fn poll_position(&self, x: &mut c_double, y: &mut c_double, a: &mut c_double, m: &mut c_int) -> bool {
+        let phase = self.phase();
+        *x = CENTER_X + RADIUS * phase.cos();
+        *y = CENTER_Y + RADIUS * phase.sin();
+        // 진행 방향 (원의 접선 방향)
+        *a = (phase.to_degrees() + 90.0) % 360.0;
+        *m = 0;
+        true
+    }
+
+    fn poll_rotation(&self, r: &mut c_double) -> bool {
+        *r = (self.phase().to_degrees() + 90.0) % 360.0;
+        true
+    }
+
+    fn last_error(&self) -> String {
+        String::from("{}")
+    }
+
+    fn version(&self) -> String {
+        String::from("mock")
+    }
+}
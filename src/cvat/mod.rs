@@ -4,30 +4,34 @@
  * 이를 inline module로 둠으로써, cvat::함수명 으로 접근 가능하도록 한다.
  */
 pub mod bindings;
+mod backend;
+mod delta;
 mod error;
+mod filter;
+mod mock;
+mod recorder;
+mod replay;
 mod tracking;
 mod translations;
 mod features;
 
-pub use error::{CvatError, Result};
+pub use backend::{create_backend, validate_library, TrackingBackend};
+pub use delta::DeltaConfig;
+pub use error::Result;
+pub use filter::FilterConfig;
+pub use recorder::Recorder;
 pub use tracking::Tracker;
 pub use features::*;
 
-use crate::models::{AppEvent, SendEvent, WsEvent};
-use crate::app::path::get_lib_path;
+use crate::models::{AppConfig, AppEvent, SendEvent, WsEvent};
+use crate::app::config::ConfigManager;
 use crate::events::EventBus;
 use std::error::Error;
-use std::ffi::CStr;
 use crate::websocket::WebSocketHandler;
 use std::sync::Arc;
 use crate::app::get_app_state;
 
-pub fn is_cvat_loaded() -> bool {
-    let state = get_app_state();
-    state.get_instance().is_some()
-}
-
-pub fn initialize_cvat() -> Result<()> {
+pub fn initialize_cvat(config: &AppConfig) -> Result<()> {
     log::debug!("Initialize Cvat");
     let state = get_app_state();
     
@@ -36,26 +40,43 @@ pub fn initialize_cvat() -> Result<()> {
         return Ok(());
     }
 
-    let cvat = unsafe { 
-        bindings::cvAutoTrack::new(get_lib_path().join("cvAutoTrack.dll").to_str().unwrap())
-    }.map_err(|e| CvatError::LibraryError(e.to_string()))?;
+    let backend = create_backend(config)?;
 
-    state.set_instance(Some(cvat));
+    state.set_instance(Some(backend));
     Ok(())
 }
 
-pub fn unload_cvat() -> Result<()> {
+// 백엔드를 준비하고 추적을 시작한다. 시작했다면 doneInit을 알린다.
+pub async fn start_cvat(
+    event_bus: &Arc<EventBus>,
+    ws_handler: &Arc<WebSocketHandler>,
+) -> std::result::Result<(), Box<dyn Error + Send + Sync>> {
+    let config = ConfigManager::global().get().await;
+    initialize_cvat(&config).map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+    if start_track_thread(event_bus.clone(), ws_handler.clone()) {
+        if config.record_tracking {
+            Recorder::global().start(config.record_max_file_size)?;
+        }
+        ws_handler.broadcast(SendEvent::from(WsEvent::DoneInit)).await?;
+    }
+    Ok(())
+}
+
+pub async fn unload_cvat() -> Result<()> {
     let state = get_app_state();
     log::debug!("Unloading CVAT...");
     
-    // instance를 먼저 가져와서 Option<cvAutoTrack>으로 소유권 이전
-    let mut instance = state.get_instance();
-    if let Some(cvat) = instance.as_ref() {
-        unsafe { cvat.close(); }
+    // 추적 스레드가 백엔드의 참조를 들고 있으므로, 스레드를 먼저 종료시킨다.
+    // 라이브러리는 마지막 참조가 사라질 때 해제되므로, 스레드가 끝날 때까지 기다린다.
+    state.set_tracking(false);
+    if let Some(tracker_thread) = state.take_tracker_thread() {
+        if let Err(e) = tracker_thread.await {
+            log::error!("Tracking Thread: 종료 실패");
+            log::error!("Error: {}", e);
+        }
     }
     
     // instance를 None으로 설정
-    drop(instance);  // 명시적으로 읽기 lock 해제
     state.set_instance(None);
     
     log::debug!("CVAT unloaded successfully");
@@ -73,11 +94,7 @@ pub async fn register_events(
         let event_bus = event_bus1.clone();
         let ws_handler = ws_handler1.clone();
         async move {
-            initialize_cvat().map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
-            if start_track_thread(event_bus.clone(), ws_handler.clone()) {
-                ws_handler.broadcast(SendEvent::from(WsEvent::DoneInit)).await?;
-            }
-            Ok(())
+            start_cvat(&event_bus, &ws_handler).await
         }
     }).await?;
     
@@ -85,7 +102,7 @@ pub async fn register_events(
     ws_handler.register("uninit", move |_, _| {
         let event_bus = event_bus2.clone();
         async move {
-            unload_cvat().map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            unload_cvat().await.map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
             event_bus.emit(&AppEvent::DoneUninit()).await.unwrap();
             Ok(())
         }
@@ -96,27 +113,58 @@ pub async fn register_events(
         let event_bus = event_bus3.clone();
         async move {
             log::debug!("Uninit Event");
-            unload_cvat().map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            unload_cvat().await.map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
             event_bus.emit(&AppEvent::DoneUninit()).await.unwrap();
             Ok(())
         }
     }).await?;
 
+    let ws_handler_rec = ws_handler.clone();
+    ws_handler.register("startRecording", move |id, _| {
+        let ws_handler = ws_handler_rec.clone();
+        async move {
+            let config = ConfigManager::global().get().await;
+            let info = Recorder::global().start(config.record_max_file_size)?;
+            ws_handler.send_to(id, SendEvent::from(WsEvent::Recording { info })).await?;
+            Ok(())
+        }
+    }).await?;
+
+    let ws_handler_rec = ws_handler.clone();
+    ws_handler.register("stopRecording", move |id, _| {
+        let ws_handler = ws_handler_rec.clone();
+        async move {
+            let info = Recorder::global().stop();
+            ws_handler.send_to(id, SendEvent::from(WsEvent::Recording { info })).await?;
+            Ok(())
+        }
+    }).await?;
+
+    // 설정에서 기록을 켜고 끄는 경우, 추적중이라면 바로 반영한다.
+    ConfigManager::global().register_handler(|old_config, new_config| {
+        if old_config.record_tracking == new_config.record_tracking {
+            return;
+        }
+        if !new_config.record_tracking {
+            Recorder::global().stop();
+        } else if get_app_state().is_tracking() {
+            if let Err(e) = Recorder::global().start(new_config.record_max_file_size) {
+                log::error!("Recording: 시작 실패");
+                log::error!("Error: {}", e);
+            }
+        }
+    }).await;
+
     // ... 다른 이벤트 핸들러들
     Ok(())
 }
 
 pub fn get_cvat_version() -> String {
     let state = get_app_state();
-    let instance = state.get_instance();
-    if let Some(cvat) = instance.as_ref() {
-        let mut c_buf: [i8; 256] = [0; 256];
-        unsafe { cvat.GetCompileVersion(c_buf.as_mut_ptr(), 256); }
-        let c_str: &CStr = unsafe { CStr::from_ptr(c_buf.as_ptr()) };
-        let str_slice: &str = c_str.to_str().unwrap();
-        return str_slice.to_string();
+    if let Some(backend) = state.get_instance() {
+        return backend.version();
     }
-    return String::new();
+    String::new()
 }
 
 #[cfg(test)]
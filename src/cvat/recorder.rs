@@ -0,0 +1,147 @@
+use crate::app::path::get_recordings_path;
+use crate::models::{RecordingInfo, TrackData, TrackSample};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+static RECORDER: Lazy<Recorder> = Lazy::new(|| Recorder::with_dir(get_recordings_path()));
+
+/*
+ * 추적 스레드가 broadcast하는 TrackData를 JSON Lines 파일로 기록한다.
+ * 파일 형식은 replay 백엔드가 그대로 재생할 수 있는 TrackSample이며,
+ * 한 파일이 record_max_file_size를 넘으면 같은 이름에 번호를 붙인 새 파일로 이어서 기록한다.
+ */
+pub struct Recorder {
+    // 기록 파일을 둘 디렉토리
+    dir: PathBuf,
+    session: Mutex<Option<RecordingSession>>,
+}
+
+struct RecordingSession {
+    dir: PathBuf,
+    name: String,
+    started_at: Instant,
+    max_file_size: u64,
+    part: u32,
+    path: PathBuf,
+    file: File,
+    written: u64,
+    samples: u64,
+}
+
+impl Recorder {
+    pub(crate) fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            session: Mutex::new(None),
+        }
+    }
+
+    pub fn global() -> &'static Recorder {
+        &RECORDER
+    }
+
+    // 이미 기록중이라면 기존 기록을 이어간다.
+    pub fn start(&self, max_file_size_kb: u32) -> std::io::Result<RecordingInfo> {
+        let mut session = self.session.lock();
+        if session.is_none() {
+            let name = chrono::DateTime::<chrono::Utc>::from(SystemTime::now())
+                .format("track-%Y%m%d-%H%M%S")
+                .to_string();
+            let new_session = RecordingSession::open(self.dir.clone(), name, max_file_size_kb as u64 * 1024)?;
+            log::debug!("Recording: 시작 {}", new_session.path.display());
+            *session = Some(new_session);
+        }
+        Ok(Recorder::info(session.as_ref()))
+    }
+
+    pub fn stop(&self) -> RecordingInfo {
+        let mut session = self.session.lock();
+        let info = Recorder::info(session.as_ref());
+        if let Some(s) = session.take() {
+            log::debug!("Recording: 종료 {} ({} samples)", s.path.display(), s.samples);
+        }
+        RecordingInfo {
+            recording: false,
+            ..info
+        }
+    }
+
+    pub fn record(&self, data: &TrackData) {
+        let mut session = self.session.lock();
+        if let Some(s) = session.as_mut() {
+            if let Err(e) = s.write(data) {
+                log::error!("Recording: 기록 실패, 기록을 중단합니다.");
+                log::error!("Error: {}", e);
+                *session = None;
+            }
+        }
+    }
+
+    fn info(session: Option<&RecordingSession>) -> RecordingInfo {
+        match session {
+            Some(s) => RecordingInfo {
+                recording: true,
+                file: s.path.to_string_lossy().to_string(),
+                samples: s.samples,
+            },
+            None => RecordingInfo::default(),
+        }
+    }
+}
+
+impl RecordingSession {
+    fn open(dir: PathBuf, name: String, max_file_size: u64) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let path = RecordingSession::part_path(&dir, &name, 0);
+        let file = File::create(&path)?;
+        Ok(Self {
+            dir,
+            name,
+            started_at: Instant::now(),
+            max_file_size,
+            part: 0,
+            path,
+            file,
+            written: 0,
+            samples: 0,
+        })
+    }
+
+    fn part_path(dir: &Path, name: &str, part: u32) -> PathBuf {
+        if part == 0 {
+            dir.join(format!("{}.jsonl", name))
+        } else {
+            dir.join(format!("{}.{}.jsonl", name, part))
+        }
+    }
+
+    fn write(&mut self, data: &TrackData) -> std::io::Result<()> {
+        let sample = TrackSample {
+            t: self.started_at.elapsed().as_millis() as u64,
+            data: data.clone(),
+        };
+        let mut line = serde_json::to_string(&sample)?;
+        line.push('\n');
+
+        if self.written > 0 && self.written + line.len() as u64 > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.written += line.len() as u64;
+        self.samples += 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.part += 1;
+        self.path = RecordingSession::part_path(&self.dir, &self.name, self.part);
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        log::debug!("Recording: 다음 파일 {}", self.path.display());
+        Ok(())
+    }
+}
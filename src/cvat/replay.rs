@@ -0,0 +1,152 @@
+use super::backend::TrackingBackend;
+use super::error::*;
+use crate::models::TrackSample;
+use libc::{c_double, c_int};
+use parking_lot::Mutex;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+/*
+ * 기록된 TrackSample 파일(JSON Lines)을 재생하는 백엔드.
+ * 위치를 요청할 때마다 다음 샘플을 순서대로 돌려주고, 다음 샘플까지의 대기 시간은
+ * 기록된 t의 차이를 배속으로 나눈 값으로 알려준다(next_delay).
+ * capture_interval로 다시 샘플링하지 않으므로 기록된 샘플이 빠짐없이 원래 간격대로 broadcast된다.
+ */
+pub struct ReplayBackend {
+    samples: Vec<TrackSample>,
+    speed: f64,
+    repeat: bool,
+    // 다음 poll_position에서 돌려줄 샘플의 index
+    cursor: Mutex<usize>,
+    current: Mutex<Option<usize>>,
+}
+
+impl ReplayBackend {
+    pub fn new(mut samples: Vec<TrackSample>, speed: f64, repeat: bool) -> Self {
+        samples.sort_by_key(|s| s.t);
+        Self {
+            samples,
+            speed: if speed > 0.0 { speed } else { 1.0 },
+            repeat,
+            cursor: Mutex::new(0),
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn from_file(path: &Path, speed: f64, repeat: bool) -> Result<Self> {
+        log::debug!("Replay File: {}", path.display());
+        let file = File::open(path)
+            .map_err(|e| CvatError::Initialization(format!("{}: {}", path.display(), e)))?;
+        let samples = ReplayBackend::parse(BufReader::new(file))?;
+        log::debug!("Replay Samples: {}", samples.len());
+        Ok(ReplayBackend::new(samples, speed, repeat))
+    }
+
+    // 한 줄에 하나의 TrackSample JSON. 빈 줄은 무시한다.
+    pub fn parse(reader: impl BufRead) -> Result<Vec<TrackSample>> {
+        let mut samples = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| CvatError::Initialization(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample: TrackSample = serde_json::from_str(&line)
+                .map_err(|e| CvatError::Initialization(format!("line {}: {}", i + 1, e)))?;
+            samples.push(sample);
+        }
+        Ok(samples)
+    }
+
+    // 다음에 보여줄 샘플의 index를 가져오고 cursor를 한 칸 옮긴다.
+    fn advance(&self) -> Option<usize> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut cursor = self.cursor.lock();
+        let index = *cursor;
+        if index + 1 < self.samples.len() {
+            *cursor = index + 1;
+        } else if self.repeat {
+            *cursor = 0;
+        }
+        // 반복하지 않으면 마지막 샘플에 머무른다.
+        Some(index)
+    }
+}
+
+impl TrackingBackend for ReplayBackend {
+    fn init(&self) -> bool {
+        *self.cursor.lock() = 0;
+        *self.current.lock() = None;
+        !self.samples.is_empty()
+    }
+
+    fn uninit(&self) -> bool {
+        true
+    }
+
+    fn poll_position(&self, x: &mut c_double, y: &mut c_double, a: &mut c_double, m: &mut c_int) -> bool {
+        let index = self.advance();
+        *self.current.lock() = index;
+        match index.map(|i| &self.samples[i]) {
+            Some(sample) if sample.data.err.is_empty() => {
+                // 필터가 적용된 기록이라면 원본 값을 재생한다.
+                match sample.data.raw {
+                    Some(raw) => {
+                        *x = raw.x;
+                        *y = raw.y;
+                        *a = raw.a;
+                    }
+                    None => {
+                        *x = sample.data.x;
+                        *y = sample.data.y;
+                        *a = sample.data.a;
+                    }
+                }
+                *m = sample.data.m;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn poll_rotation(&self, r: &mut c_double) -> bool {
+        match *self.current.lock() {
+            Some(i) => {
+                let data = &self.samples[i].data;
+                *r = data.raw.map(|raw| raw.r).unwrap_or(data.r);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn last_error(&self) -> String {
+        match *self.current.lock() {
+            Some(i) if !self.samples[i].data.err.is_empty() => self.samples[i].data.err.clone(),
+            _ if self.samples.is_empty() => {
+                json!({ "errorList": [{ "code": -1, "msg": "재생할 기록이 없습니다." }] }).to_string()
+            }
+            _ => String::from("{}"),
+        }
+    }
+
+    fn version(&self) -> String {
+        String::from("replay")
+    }
+
+    // 마지막으로 돌려준 샘플과 다음 샘플의 기록 시각 차이 / 배속.
+    // 반복으로 처음으로 돌아가거나 마지막 샘플에 머무를 때는 Tracker의 기본 간격을 쓴다.
+    fn next_delay(&self) -> Option<Duration> {
+        let current = (*self.current.lock())?;
+        let next = *self.cursor.lock();
+        if next <= current {
+            return None;
+        }
+        let gap = self.samples[next].t - self.samples[current].t;
+        Some(Duration::from_secs_f64(gap as f64 / 1000.0 / self.speed))
+    }
+}
@@ -4,6 +4,7 @@ mod lib_tests {
     use crate::{cvat::bindings::cvAutoTrack, models};
 
     #[test]
+    #[ignore = "lib/bin/cvAutoTrack.dll과 실행중인 원신이 필요함"]
     fn libload() {
         let mut d: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         d.push("lib/bin/cvAutoTrack.dll");
@@ -88,4 +89,315 @@ mod lib_tests {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use std::time::Duration;
+    use libc::{c_double, c_int};
+    use crate::cvat::TrackingBackend;
+    use crate::cvat::replay::ReplayBackend;
+
+    const RECORDING: &str = r#"
+{"t":1000,"x":10.0,"y":20.0,"a":90.0,"r":45.0,"m":0,"err":""}
+{"t":1500,"x":11.0,"y":21.0,"a":91.0,"r":46.0,"m":0,"err":""}
+
+{"t":2000,"x":0.0,"y":0.0,"a":0.0,"r":0.0,"m":0,"err":"{\"errorList\":[{\"code\":1001,\"msg\":\"paimon\"}]}"}
+{"t":3000,"x":12.0,"y":22.0,"a":92.0,"r":47.0,"m":1}
+"#;
+
+    fn replay(speed: f64, repeat: bool) -> ReplayBackend {
+        let samples = ReplayBackend::parse(RECORDING.as_bytes()).expect("recording parse failed.");
+        ReplayBackend::new(samples, speed, repeat)
+    }
+
+    #[test]
+    fn parse_recording() {
+        let samples = ReplayBackend::parse(RECORDING.as_bytes()).unwrap();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[3].data.m, 1);
+        assert!(samples[3].data.err.is_empty());
+        assert!(ReplayBackend::parse("{\"t\":0}\nnot json".as_bytes()).is_err());
+    }
+
+    // 샘플을 하나 가져오고, 그 다음 샘플까지의 대기 시간을 함께 돌려준다.
+    fn step(backend: &ReplayBackend) -> (c_double, Option<Duration>) {
+        let (mut x, mut y, mut a, mut m): (c_double, c_double, c_double, c_int) = Default::default();
+        backend.poll_position(&mut x, &mut y, &mut a, &mut m);
+        (x, backend.next_delay())
+    }
+
+    #[test]
+    fn sample_timing() {
+        let backend = replay(1.0, false);
+        assert!(backend.init());
+        assert_eq!(step(&backend), (10.0, Some(Duration::from_millis(500))));
+        assert_eq!(step(&backend), (11.0, Some(Duration::from_millis(500))));
+        // 에러 샘플도 기록된 간격대로 재생한다.
+        assert_eq!(step(&backend).1, Some(Duration::from_millis(1000)));
+        // 반복하지 않으면 마지막 샘플에 머무르고 기본 간격을 따른다.
+        assert_eq!(step(&backend), (12.0, None));
+        assert_eq!(step(&backend), (12.0, None));
+    }
+
+    #[test]
+    fn sample_timing_with_speed_and_loop() {
+        let backend = replay(2.0, true);
+        assert!(backend.init());
+        assert_eq!(step(&backend), (10.0, Some(Duration::from_millis(250))));
+        assert_eq!(step(&backend), (11.0, Some(Duration::from_millis(250))));
+        assert_eq!(step(&backend).1, Some(Duration::from_millis(500)));
+        // 마지막 샘플 다음에는 처음으로 돌아간다.
+        assert_eq!(step(&backend), (12.0, None));
+        assert_eq!(step(&backend), (10.0, Some(Duration::from_millis(250))));
+    }
+
+    #[test]
+    fn empty_recording() {
+        let backend = ReplayBackend::new(Vec::new(), 1.0, true);
+        assert!(!backend.init());
+        let (mut x, mut y, mut a, mut m): (c_double, c_double, c_double, c_int) = Default::default();
+        assert!(!backend.poll_position(&mut x, &mut y, &mut a, &mut m));
+        assert!(backend.last_error().contains("errorList"));
+    }
+
+    #[test]
+    fn poll_first_sample() {
+        let backend = replay(1.0, false);
+        assert!(backend.init());
+        let (mut x, mut y, mut a, mut r, mut m): (c_double, c_double, c_double, c_double, c_int) = Default::default();
+        assert!(backend.poll_position(&mut x, &mut y, &mut a, &mut m));
+        assert!(backend.poll_rotation(&mut r));
+        assert_eq!((x, y, a, r, m), (10.0, 20.0, 90.0, 45.0, 0));
+        assert_eq!(backend.version(), "replay");
+    }
+}
+
+// filter_tests, delta_tests에서 함께 쓰는 도우미
+#[cfg(test)]
+mod support {
+    use crate::models::{AppConfig, TrackData};
+
+    pub fn track(x: f64, y: f64, a: f64, m: i32) -> TrackData {
+        TrackData { x, y, a, m, ..Default::default() }
+    }
+
+    // 테스트에 필요한 항목만 바꾼 AppConfig로 FilterConfig, DeltaConfig를 만든다.
+    pub fn config<T: for<'a> From<&'a AppConfig>>(config: AppConfig) -> T {
+        T::from(&config)
+    }
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use std::time::{Duration, Instant};
+    use super::support::{config, track};
+    use crate::cvat::FilterConfig;
+    use crate::cvat::filter::TrackFilter;
+    use crate::models::AppConfig;
+
+    fn filter_config() -> FilterConfig {
+        config(AppConfig {
+            filter_enabled: true,
+            filter_smoothing: 50,
+            filter_max_speed: 100,
+            filter_reject_limit: 2,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn smooths_position_and_keeps_raw() {
+        let config = filter_config();
+        let mut filter = TrackFilter::new();
+        let start = Instant::now();
+
+        let mut first = track(0.0, 0.0, 0.0, 0);
+        filter.apply(&config, &mut first, start);
+        assert_eq!((first.x, first.y), (0.0, 0.0));
+
+        let mut second = track(10.0, 20.0, 0.0, 0);
+        filter.apply(&config, &mut second, start + Duration::from_secs(1));
+        assert_eq!((second.x, second.y), (5.0, 10.0));
+        let raw = second.raw.expect("raw value must be kept");
+        assert_eq!((raw.x, raw.y), (10.0, 20.0));
+    }
+
+    #[test]
+    fn rejects_teleport_until_limit() {
+        let config = filter_config();
+        let mut filter = TrackFilter::new();
+        let start = Instant::now();
+        filter.apply(&config, &mut track(0.0, 0.0, 0.0, 0), start);
+
+        for i in 1..=2 {
+            let mut jump = track(5000.0, 0.0, 0.0, 0);
+            filter.apply(&config, &mut jump, start + Duration::from_millis(250 * i));
+            assert_eq!(jump.x, 0.0);
+        }
+        let mut jump = track(5000.0, 0.0, 0.0, 0);
+        filter.apply(&config, &mut jump, start + Duration::from_millis(750));
+        assert_eq!(jump.x, 5000.0);
+    }
+
+    #[test]
+    fn map_change_resets() {
+        let config = filter_config();
+        let mut filter = TrackFilter::new();
+        let start = Instant::now();
+        filter.apply(&config, &mut track(0.0, 0.0, 0.0, 0), start);
+
+        let mut other_map = track(5000.0, 5000.0, 0.0, 1);
+        filter.apply(&config, &mut other_map, start + Duration::from_millis(250));
+        assert_eq!((other_map.x, other_map.y), (5000.0, 5000.0));
+    }
+
+    #[test]
+    fn disabled_filter_passes_through() {
+        let config: FilterConfig = config(AppConfig::default());
+        let mut filter = TrackFilter::new();
+        let mut data = track(1.0, 2.0, 0.0, 0);
+        filter.apply(&config, &mut data, Instant::now());
+        assert_eq!((data.x, data.y), (1.0, 2.0));
+        assert!(data.raw.is_none());
+    }
+
+    #[test]
+    fn smoothed_angle_wraps() {
+        let config = filter_config();
+        let mut filter = TrackFilter::new();
+        let start = Instant::now();
+        filter.apply(&config, &mut track(0.0, 0.0, 10.0, 0), start);
+
+        let mut data = track(0.0, 0.0, 350.0, 0);
+        filter.apply(&config, &mut data, start + Duration::from_millis(250));
+        assert_eq!(data.a, 0.0);
+    }
+
+    #[test]
+    fn smoothed_angle_keeps_signed_range() {
+        let config = filter_config();
+        let mut filter = TrackFilter::new();
+        let start = Instant::now();
+        filter.apply(&config, &mut track(0.0, 0.0, 170.0, 0), start);
+
+        // ±180 경계를 넘어가면 가까운 방향으로 보간하고, -180..180 범위로 돌려준다.
+        let mut data = track(0.0, 0.0, -170.0, 0);
+        filter.apply(&config, &mut data, start + Duration::from_millis(250));
+        assert_eq!(data.a, -180.0);
+
+        let mut data = track(0.0, 0.0, -150.0, 0);
+        filter.apply(&config, &mut data, start + Duration::from_millis(500));
+        assert_eq!(data.a, -165.0);
+    }
+}
+
+#[cfg(test)]
+mod delta_tests {
+    use std::time::{Duration, Instant};
+    use super::support::{config, track};
+    use crate::cvat::DeltaConfig;
+    use crate::cvat::delta::DeltaEncoder;
+    use crate::models::{AppConfig, WsEvent};
+
+    fn delta_config(delta_payload: bool) -> DeltaConfig {
+        config(AppConfig {
+            track_change_only: true,
+            track_min_distance: 2,
+            track_min_angle: 5,
+            track_keepalive: 1000,
+            track_delta_payload: delta_payload,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn always_sends_when_disabled() {
+        let config: DeltaConfig = config(AppConfig::default());
+        let mut encoder = DeltaEncoder::new();
+        let now = Instant::now();
+        assert!(encoder.next(&config, &track(0.0, 0.0, 0.0, 0), now).is_some());
+        assert!(encoder.next(&config, &track(0.0, 0.0, 0.0, 0), now).is_some());
+    }
+
+    #[test]
+    fn skips_small_changes_until_keepalive() {
+        let config = delta_config(false);
+        let mut encoder = DeltaEncoder::new();
+        let start = Instant::now();
+        assert!(matches!(encoder.next(&config, &track(0.0, 0.0, 0.0, 0), start), Some(WsEvent::Track { .. })));
+        assert!(encoder.next(&config, &track(1.0, 0.0, 359.0, 0), start + Duration::from_millis(250)).is_none());
+        assert!(matches!(
+            encoder.next(&config, &track(3.0, 0.0, 0.0, 0), start + Duration::from_millis(500)),
+            Some(WsEvent::Track { .. })
+        ));
+        assert!(matches!(
+            encoder.next(&config, &track(3.0, 0.0, 0.0, 0), start + Duration::from_millis(1500)),
+            Some(WsEvent::Track { .. })
+        ));
+    }
+
+    #[test]
+    fn sends_only_changed_fields() {
+        let config = delta_config(true);
+        let mut encoder = DeltaEncoder::new();
+        let start = Instant::now();
+        encoder.next(&config, &track(0.0, 0.0, 0.0, 0), start);
+        match encoder.next(&config, &track(0.0, 0.0, 10.0, 0), start + Duration::from_millis(250)) {
+            Some(WsEvent::TrackDelta { data }) => {
+                assert_eq!(data.a, Some(10.0));
+                assert_eq!((data.x, data.y, data.r, data.m, data.err), (None, None, None, None, None));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        // 작은 변화가 누적되면 보낸다.
+        assert!(encoder.next(&config, &track(1.5, 0.0, 10.0, 0), start + Duration::from_millis(500)).is_none());
+        assert!(matches!(
+            encoder.next(&config, &track(2.5, 0.0, 10.0, 0), start + Duration::from_millis(750)),
+            Some(WsEvent::TrackDelta { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod recorder_tests {
+    use std::fs::File;
+    use std::io::BufReader;
+    use super::support::track;
+    use crate::cvat::recorder::Recorder;
+    use crate::cvat::replay::ReplayBackend;
+
+    #[test]
+    fn rotate_and_parse_back() {
+        let dir = std::env::temp_dir().join(format!("gpa-recorder-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = Recorder::with_dir(dir.clone());
+
+        // 1KB 제한을 여러 번 넘길 만큼 기록한다.
+        recorder.start(1).unwrap();
+        for i in 0..100 {
+            recorder.record(&track(i as f64, i as f64 * 2.0, 90.0, 0));
+        }
+        let info = recorder.stop();
+        assert!(!info.recording);
+        assert_eq!(info.samples, 100);
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+            .collect();
+        files.sort();
+        assert!(files.len() > 1, "rotation did not happen: {:?}", files);
+
+        let mut total = 0;
+        for path in &files {
+            assert!(std::fs::metadata(path).unwrap().len() <= 1024);
+            let samples = ReplayBackend::parse(BufReader::new(File::open(path).unwrap())).unwrap();
+            total += samples.len();
+        }
+        assert_eq!(total, 100);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
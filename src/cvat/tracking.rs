@@ -1,34 +1,40 @@
 use super::error::*;
 use super::translations::translate_error_json;
-use super::bindings::cvAutoTrack;
+use super::backend::TrackingBackend;
+use super::delta::DeltaEncoder;
+use super::filter::TrackFilter;
+use super::recorder::Recorder;
 use crate::app::get_app_state;
 use crate::models::{SendEvent, TrackData, WsEvent};
 use crate::websocket::WebSocketHandler;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use libc::{c_double, c_int};
-use std::ffi::CStr;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use tokio::runtime::Runtime;
 
-pub struct Tracker<'a> {
-    cvat: &'a cvAutoTrack,
+pub struct Tracker {
+    backend: Arc<dyn TrackingBackend>,
 }
 
-impl<'a> Tracker<'a> {
-    pub fn new(cvat: &'a cvAutoTrack) -> Self {
-        Self { 
-            cvat
+impl Tracker {
+    pub fn new(backend: Arc<dyn TrackingBackend>) -> Self {
+        Self {
+            backend
         }
     }
 
     pub fn start(&self, ws_handler: Arc<WebSocketHandler>) -> Result<()> {
         log::debug!("Start Track");
 
+        if !self.backend.init() {
+            log::debug!("Tracking Backend init failed: {}", Tracker::get_last_error(self.backend.as_ref()));
+        }
+
         let state = get_app_state();
         state.set_tracking(true);
-        
+
         // Arc<AtomicBool|AtomicU32>로 선언. 이는 여러 스레드에서 공유되는 동일한 값을 가리킨다.
         // 이를 통해 여러 스레드에서 동일한 값을 읽고 쓰는 것을 보장한다.
         // state.set_tracking(false)를 호출하면 내부적으로 동일한 Arc<AtomicBool>을 업데이트함.
@@ -36,69 +42,73 @@ impl<'a> Tracker<'a> {
         let interval = Arc::clone(&state.capture_interval);
         let delay = Arc::clone(&state.capture_delay_on_error);
         let is_tracking = Arc::clone(&state.is_tracking);
-        
-        let cvat = unsafe { &*(self.cvat as *const _) };
+        let filter_config = Arc::clone(&state.filter_config);
+        let delta_config = Arc::clone(&state.delta_config);
+
+        // 스레드가 끝날 때까지 백엔드(라이브러리)가 해제되지 않도록 Arc를 넘긴다.
+        let backend = Arc::clone(&self.backend);
         let ws_handler_thread = ws_handler.clone();
-        
+
         // spawn_blocking을 사용하여 별도 스레드에서 실행
-        tokio::task::spawn_blocking(move || {
+        let tracker_thread = tokio::task::spawn_blocking(move || {
             let rt = Runtime::new().unwrap();
             log::debug!("Tracking Thread Started");
-            
+
+            let mut filter = TrackFilter::new();
+            let mut encoder = DeltaEncoder::new();
             while is_tracking.load(Ordering::Relaxed) {
                 let mut trackdata = TrackData::default();
-                match Tracker::track(cvat, &mut trackdata.x, &mut trackdata.y, &mut trackdata.a, 
+                let wait = match Tracker::track(backend.as_ref(), &mut trackdata.x, &mut trackdata.y, &mut trackdata.a,
                     &mut trackdata.r, &mut trackdata.m) {
-                    Ok(_) => {
-                        let _ = rt.block_on(ws_handler_thread.broadcast(
-                            SendEvent::from(WsEvent::Track { data: trackdata })
-                        ));
-                        thread::sleep(Duration::from_millis(
-                            interval.load(Ordering::Relaxed).into()
-                        ));
-                    },
+                    Ok(_) => interval.load(Ordering::Relaxed),
                     Err(e) => {
                         trackdata.err = e.to_string();
-                        let _ = rt.block_on(ws_handler_thread.broadcast(
-                            SendEvent::from(WsEvent::Track { data: trackdata })
-                        ));
-                        thread::sleep(Duration::from_millis(
-                            delay.load(Ordering::Relaxed).into()
-                        ));
+                        delay.load(Ordering::Relaxed)
                     }
+                };
+                let now = Instant::now();
+                filter.apply(&filter_config.read(), &mut trackdata, now);
+                Recorder::global().record(&trackdata);
+                ws_handler_thread.set_latest_track(Some(&trackdata));
+                // 변경 사항이 없다면 보내지 않는다.
+                if let Some(event) = encoder.next(&delta_config.read(), &trackdata, now) {
+                    let _ = rt.block_on(ws_handler_thread.broadcast(SendEvent::from(event)));
                 }
+                // replay처럼 샘플 간격이 정해진 백엔드는 그 간격을 따른다.
+                let wait = backend.next_delay().unwrap_or(Duration::from_millis(wait.into()));
+                thread::sleep(wait);
             }
-            unsafe { cvat.uninit() };
+            backend.uninit();
+            Recorder::global().stop();
             state.set_tracking(false);
+            ws_handler_thread.set_latest_track(None);
             log::debug!("Tracking Thread Stopped");
             let _ = rt.block_on(ws_handler_thread.broadcast(SendEvent::from(WsEvent::Uninit {})));
         });
-        
+        state.set_tracker_thread(tracker_thread);
+
         Ok(())
     }
 
     fn track(
-        cvat: &cvAutoTrack,
+        backend: &dyn TrackingBackend,
         x: &mut c_double,
         y: &mut c_double,
         a: &mut c_double,
         r: &mut c_double,
         m: &mut c_int,
     ) -> Result<()> {
-        if !unsafe { cvat.GetTransformOfMap(x, y, a, m) } {
-            return Err(CvatError::TrackingError(Tracker::get_last_error(cvat)));
+        if !backend.poll_position(x, y, a, m) {
+            return Err(CvatError::Tracking(Tracker::get_last_error(backend)));
         }
-        if !unsafe { cvat.GetRotation(r) } {
-            return Err(CvatError::TrackingError(Tracker::get_last_error(cvat)));
+        if !backend.poll_rotation(r) {
+            return Err(CvatError::Tracking(Tracker::get_last_error(backend)));
         }
         Ok(())
     }
 
-    fn get_last_error(cvat: &cvAutoTrack) -> String {
-        let mut cs: [i8; 256] = [0; 256];
-        let c_buf: *mut i8 = cs.as_mut_ptr();
-        unsafe { cvat.GetLastErrJson(c_buf, 256) };
-        let error_json = unsafe { CStr::from_ptr(c_buf) }.to_str().unwrap_or("{}");
-        translate_error_json(error_json).unwrap_or_else(|_| error_json.to_string())
+    fn get_last_error(backend: &dyn TrackingBackend) -> String {
+        let error_json = backend.last_error();
+        translate_error_json(&error_json).unwrap_or(error_json)
     }
-} 
\ No newline at end of file
+}
@@ -183,7 +183,7 @@ pub fn translate_error_json(error_json: &str) -> Result<String, Box<dyn Error>>
                             .position(|pattern| msg.contains(pattern))
                             .map(|index| info.messages[index])
                     })
-                    .unwrap_or_else(|| msg);
+                    .unwrap_or(msg);
                 
                 json!({
                     "code": code,
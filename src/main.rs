@@ -18,8 +18,23 @@ use websocket::WebSocketHandler;
 
 #[tokio::main]
 async fn main() {
+    // --schema [파일 경로] : 프로토콜 JSON Schema를 파일(없으면 표준 출력)로 내보내고 종료한다.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a.eq("--schema")) {
+        export_schema(args.get(i + 1));
+        return;
+    }
+    // --revoke-pairings : 연결된 사이트를 모두 해제하고 종료한다. 트레이가 없는 플랫폼에서 사용한다.
+    if args.iter().any(|a| a.eq("--revoke-pairings")) {
+        revoke_pairings();
+        return;
+    }
+
+    // 디버그 모드가 아니어도 log topic을 구독한 클라이언트에게 로그를 보낼 수 있도록 logger를 먼저 등록한다.
+    let _ = app::logger::init();
+
     if is_process_already_running() {
-        let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), &format!("GPA가 이미 실행중입니다.\n추가로 실행된 프로그램은 잠시 후 종료됩니다."), true);
+        let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), "GPA가 이미 실행중입니다.\n추가로 실행된 프로그램은 잠시 후 종료됩니다.", true);
         std::thread::sleep(std::time::Duration::from_millis(5000));
         return;
     }
@@ -42,6 +57,7 @@ async fn main() {
         match app::check_proj_directory() {
             Ok(true) => {}
             Ok(false) => {
+                #[cfg(windows)]
                 if std::env::args().find(|x| x.eq("--update")).is_none() {
                     app::installer::install().unwrap();
                 }
@@ -69,24 +85,27 @@ async fn main() {
                             debug!("App Version: {}", env!("CARGO_PKG_VERSION"));
                         },
                         Err(e) => {
-                            let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), &format!("디버그 모드 설정에 실패했습니다.\n{}", e.to_string()), true);
+                            let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), &format!("디버그 모드 설정에 실패했습니다.\n{}", e), true);
                             panic!("Debug mode enable failed. {}", e)
                         },
                     }
                     log::debug!("Logging debug messages.");
                 }
+                // 설치/제거는 레지스트리를 사용하므로 Windows에서만 지원한다.
                 if a.eq("--install") || a.eq("-i") {
                     log::debug!("Install parameter found.");
+                    #[cfg(windows)]
                     match app::installer::install() {
                         Ok(_) => {},
                         Err(e) => {
-                            let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), &format!("인스톨 파라메터 수행 중 실패했습니다.\n{}", e.to_string()), true);
+                            let _ = confirm_dialog(env!("CARGO_PKG_DESCRIPTION"), &format!("인스톨 파라메터 수행 중 실패했습니다.\n{}", e), true);
                             log::error!("Error: {}", e);
                         }
                     }
                     return;
                 } else if a.eq("--uninstall") || a.eq("-u") {
                     log::debug!("Uninstall parameter found.");
+                    #[cfg(windows)]
                     match app::installer::uninstall() {
                         Ok(_) => {},
                         Err(e) => {
@@ -100,6 +119,30 @@ async fn main() {
     }
 }
 
+fn export_schema(path: Option<&String>) {
+    let schema = serde_json::to_string_pretty(&models::protocol_schema()).unwrap();
+    match path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, schema) {
+                eprintln!("Schema export failed. {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", schema),
+    }
+}
+
+fn revoke_pairings() {
+    let store = websocket::PairingStore::global();
+    for pairing in store.list() {
+        println!("{}", pairing.label());
+    }
+    if let Err(e) = store.revoke_all() {
+        eprintln!("Revoke pairings failed. {}", e);
+        std::process::exit(1);
+    }
+}
+
 fn initialize(param: Vec<&str>) {
     log::debug!("Ready function called with parameters: {:?}", param);
     
@@ -116,6 +159,10 @@ fn initialize(param: Vec<&str>) {
     
     if param.contains(&"launch") {
         log::debug!("Launch mode enable.");
+        // 라이브러리를 교체하는 중 종료되었다면 먼저 복구한다.
+        if let Err(e) = app::updater::recover_lib_dirs() {
+            log::error!("라이브러리 디렉토리 복구 실패: {}", e);
+        }
         // Ws 시작
         thread::spawn(move || {
             let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -130,6 +177,8 @@ fn initialize(param: Vec<&str>) {
                     .expect("Failed to register Updater events");
                 app::config::register_events(&event_bus, &ws_handler).await
                     .expect("Failed to register Config events");
+                websocket::register_events(&event_bus, &ws_handler).await
+                    .expect("Failed to register WebSocket events");
                 
                 // 모든 이벤트가 등록된 후 WebSocket 서비스 시작
                 websocket::serve(Arc::clone(&ws_handler)).await
@@ -138,6 +187,10 @@ fn initialize(param: Vec<&str>) {
         });
 
         // 트레이 아이콘 추가
+        #[cfg(windows)]
         app::add_tray_item();
+        // 트레이가 없는 플랫폼에서는 종료될 때까지 기다린다. 연결된 사이트는 --revoke-pairings로 해제한다.
+        #[cfg(not(windows))]
+        app::wait_for_exit();
     }
 }
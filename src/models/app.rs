@@ -1,26 +1,145 @@
 #[allow(dead_code)]
 pub use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::sync::atomic::{AtomicU32, AtomicBool, Ordering};
-use crate::cvat::bindings::cvAutoTrack;
-use parking_lot::RwLock;
+use crate::cvat::{DeltaConfig, FilterConfig, TrackingBackend};
+use parking_lot::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use crate::app::config::ConfigManager;
+use crate::websocket::normalize_origin;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AppInfo {
-    pub version: &'static str,
-    pub lib_version: &'static str,
+    pub version: String,
+    pub lib_version: String,
     pub configs: AppConfig,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+// 위치 정보를 얻어올 추적 백엔드 종류
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackingBackendKind {
+    // cvAutoTrack.dll (Windows 전용)
+    #[default]
+    Cvat,
+    // 게임 없이 가상의 위치를 만들어내는 백엔드
+    Mock,
+    // 기록된 TrackSample 파일을 재생하는 백엔드
+    Replay,
+}
+
+// 업데이트 릴리즈 정보를 가져올 곳
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ReleaseSourceKind {
+    // GitHub releases API
+    #[default]
+    Github,
+    // release_manifest_url의 정적 JSON 파일
+    Manifest,
+    // release_local_dir의 {repo}/release.json
+    Local,
+}
+
+// 업데이트할 릴리즈를 고르는 기준
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    // 정식 릴리즈만 사용한다.
+    #[default]
+    Stable,
+    // 프리릴리즈도 사용한다.
+    Beta,
+    // *_pinned_version에 지정한 버전만 사용한다.
+    Pinned,
+}
+
+// 이전 버전의 설정 파일에 없는 항목은 기본값으로 채운다.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Eq, PartialEq, Hash)]
+#[serde(default)]
 pub struct AppConfig {
     pub auto_app_update: bool,
     pub auto_lib_update: bool,
     pub capture_interval: u32,
     pub capture_delay_on_error: u32,
     pub use_bit_blt_capture_mode: bool,
+    pub tracking_backend: TrackingBackendKind,
+    // replay 백엔드가 재생할 파일. 상대 경로는 앱 디렉토리 기준
+    pub replay_file: String,
+    // 재생 배속 (%). 100이면 기록된 시간 그대로 재생
+    pub replay_speed: u32,
+    pub replay_loop: bool,
+    // 추적 시작 시 추적 데이터를 파일로 기록할지 여부
+    pub record_tracking: bool,
+    // 기록 파일 하나의 최대 크기 (KB). 넘으면 새 파일로 나눠 기록한다.
+    pub record_max_file_size: u32,
+    // 위치 스무딩 및 튀는 값 제거 필터 사용 여부
+    pub filter_enabled: bool,
+    // 스무딩 강도 (%). 0이면 스무딩하지 않는다.
+    pub filter_smoothing: u32,
+    // 초당 이동 가능한 최대 거리 (지도 좌표 기준). 0이면 제한하지 않는다.
+    pub filter_max_speed: u32,
+    // 지도(m)별 최대 이동 거리. 키는 지도 id
+    pub filter_max_speed_by_map: BTreeMap<String, u32>,
+    // 연속으로 이 횟수보다 많이 벗어나면 순간이동으로 보고 새 위치를 받아들인다.
+    pub filter_reject_limit: u32,
+    // 위치/방향/지도가 바뀌었을 때만 track 이벤트를 보낼지 여부
+    pub track_change_only: bool,
+    // 변경으로 볼 최소 이동 거리 (지도 좌표 기준)
+    pub track_min_distance: u32,
+    // 변경으로 볼 최소 각도 (도)
+    pub track_min_angle: u32,
+    // 변경이 없어도 전체 track 이벤트를 보내는 주기 (ms)
+    pub track_keepalive: u32,
+    // 변경된 항목만 담은 trackDelta 이벤트를 보낼지 여부
+    pub track_delta_payload: bool,
+    // 웹 서버가 listen 할 주소와 포트. 변경 사항은 재시작 후 적용된다.
+    pub server_host: String,
+    pub server_port: u16,
+    // 포트가 사용중일 때 다음 포트를 시도할 횟수. 0이면 시도하지 않는다.
+    pub server_port_fallback: u16,
+    // 접속을 허용할 Origin 목록 (예: https://genshin.gamedot.org)
+    pub allowed_origins: Vec<String>,
+    // 목록에 없는 Origin의 요청이 오면 사용자에게 허용 여부를 물을지 여부
+    pub prompt_unknown_origin: bool,
+    // POST /publish 사용 여부. 켜져있어도 페어링된 클라이언트만 사용할 수 있다.
+    pub publish_enabled: bool,
+    // 등록 후 이 시간(초) 안에 WebSocket으로 연결하지 않은 클라이언트는 등록이 해제된다.
+    pub register_ttl: u32,
+    // 마지막 클라이언트의 연결이 끊긴 뒤 재연결을 기다리는 시간(초). 이 동안 추적은 계속되고, 같은 id로 재연결할 수 있다.
+    pub disconnect_grace_period: u32,
+    // 유예 시간 안에 아무도 재연결하지 않으면 GPA를 종료할지 여부 (디버그 빌드에서는 추적만 종료)
+    pub exit_on_last_disconnect: bool,
+    // WebSocket ping을 보내는 주기(초). 0이면 보내지 않는다.
+    pub ping_interval: u32,
+    // 이 시간(초) 동안 아무 메세지(pong 포함)도 받지 못한 클라이언트는 연결을 끊는다. 0이면 끊지 않는다.
+    // ping_interval이 0이라면 클라이언트가 직접 ping 이벤트 등을 보내야 연결이 유지된다.
+    pub pong_timeout: u32,
+    // 클라이언트마다 쌓아둘 track 메세지 수. 넘치면 오래된 것부터 버린다.
+    pub send_queue_size: u32,
+    // 업데이트 릴리즈 정보를 가져올 곳
+    pub release_source: ReleaseSourceKind,
+    // manifest 소스의 JSON 주소. {owner}, {repo}는 저장소 이름으로 바뀐다.
+    pub release_manifest_url: String,
+    // local 소스의 디렉토리. 상대 경로는 앱 디렉토리 기준
+    pub release_local_dir: String,
+    // 서명(.sig)이 없거나 검증할 수 없는 업데이트를 거부할지 여부. 끄면 공개키와 서명이 모두 있을 때만 검증한다.
+    pub require_update_signature: bool,
+    // 업데이트 다운로드 연결 제한 시간(초)
+    pub download_connect_timeout: u32,
+    // 이 시간(초) 동안 받은 데이터가 없으면 실패로 본다. 0이면 제한하지 않는다.
+    pub download_read_timeout: u32,
+    // 다운로드 실패 시 이어받기를 시도할 횟수
+    pub download_retries: u32,
+    // GPA와 cvAutoTrack의 업데이트 채널
+    pub app_update_channel: UpdateChannel,
+    pub lib_update_channel: UpdateChannel,
+    // 채널이 pinned일 때 사용할 릴리즈 태그 (예: v1.2.2)
+    pub app_pinned_version: String,
+    pub lib_pinned_version: String,
 }
 
 impl Default for AppConfig {
@@ -31,11 +150,50 @@ impl Default for AppConfig {
             capture_interval: 250,
             capture_delay_on_error: 1000,
             use_bit_blt_capture_mode: false,
+            tracking_backend: TrackingBackendKind::default(),
+            replay_file: String::new(),
+            replay_speed: 100,
+            replay_loop: true,
+            record_tracking: false,
+            record_max_file_size: 10240,
+            filter_enabled: false,
+            filter_smoothing: 50,
+            filter_max_speed: 200,
+            filter_max_speed_by_map: BTreeMap::new(),
+            filter_reject_limit: 3,
+            track_change_only: false,
+            track_min_distance: 1,
+            track_min_angle: 1,
+            track_keepalive: 5000,
+            track_delta_payload: false,
+            server_host: String::from("127.0.0.1"),
+            server_port: 32332,
+            server_port_fallback: 10,
+            allowed_origins: vec![String::from("https://genshin.gamedot.org")],
+            prompt_unknown_origin: false,
+            publish_enabled: false,
+            register_ttl: 60,
+            disconnect_grace_period: 30,
+            exit_on_last_disconnect: true,
+            ping_interval: 15,
+            pong_timeout: 45,
+            send_queue_size: 64,
+            release_source: ReleaseSourceKind::default(),
+            release_manifest_url: String::new(),
+            release_local_dir: String::from("releases"),
+            require_update_signature: false,
+            download_connect_timeout: 10,
+            download_read_timeout: 30,
+            download_retries: 5,
+            app_update_channel: UpdateChannel::default(),
+            lib_update_channel: UpdateChannel::default(),
+            app_pinned_version: String::new(),
+            lib_pinned_version: String::new(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateInfo {
     pub target_type: String,
@@ -57,7 +215,7 @@ pub enum AppEvent {
     Uninit(),
     DoneUninit(),
     GetConfig(String),
-    SetConfig(AppConfig, String),
+    SetConfig(Box<AppConfig>, String),
     CheckLibUpdate(String, bool),
     CheckAppUpdate(String, bool),
 }
@@ -66,7 +224,12 @@ pub struct AppState {
     pub capture_interval: Arc<AtomicU32>,
     pub capture_delay_on_error: Arc<AtomicU32>,
     pub is_tracking: Arc<AtomicBool>,
-    instance: RwLock<Option<cvAutoTrack>>,
+    pub filter_config: Arc<RwLock<FilterConfig>>,
+    pub delta_config: Arc<RwLock<DeltaConfig>>,
+    pub allowed_origins: Arc<RwLock<Vec<String>>>,
+    instance: RwLock<Option<Arc<dyn TrackingBackend>>>,
+    // 실행 중인 추적 스레드. 라이브러리를 교체하기 전에 종료를 기다린다.
+    tracker_thread: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl AppState {
@@ -74,12 +237,19 @@ impl AppState {
         let capture_interval = Arc::new(AtomicU32::new(250));
         let capture_delay_on_error = Arc::new(AtomicU32::new(1000));
         let is_tracking = Arc::new(AtomicBool::new(false));
+        let filter_config = Arc::new(RwLock::new(FilterConfig::from(&AppConfig::default())));
+        let delta_config = Arc::new(RwLock::new(DeltaConfig::from(&AppConfig::default())));
+        let allowed_origins = Arc::new(RwLock::new(AppConfig::default().allowed_origins));
 
         let state = Self {
             capture_interval: Arc::clone(&capture_interval),
             capture_delay_on_error: Arc::clone(&capture_delay_on_error),
             is_tracking,
+            filter_config: Arc::clone(&filter_config),
+            delta_config: Arc::clone(&delta_config),
+            allowed_origins: Arc::clone(&allowed_origins),
             instance: RwLock::new(None),
+            tracker_thread: Mutex::new(None),
         };
 
         // 설정 변경 핸들러는 별도로 등록
@@ -87,19 +257,31 @@ impl AppState {
             ConfigManager::global().register_handler(move |_, new_config| {
                 capture_interval.store(new_config.capture_interval, Ordering::Relaxed);
                 capture_delay_on_error.store(new_config.capture_delay_on_error, Ordering::Relaxed);
+                *filter_config.write() = FilterConfig::from(new_config);
+                *delta_config.write() = DeltaConfig::from(new_config);
+                *allowed_origins.write() = new_config.allowed_origins.clone();
             }).await;
         });
 
         state
     }
 
-    // 캡처 관련 메서드
-    pub fn get_capture_interval(&self) -> u32 {
-        self.capture_interval.load(Ordering::Relaxed)
+    pub fn set_filter_config(&self, config: FilterConfig) {
+        *self.filter_config.write() = config;
+    }
+
+    pub fn set_delta_config(&self, config: DeltaConfig) {
+        *self.delta_config.write() = config;
+    }
+
+    pub fn set_allowed_origins(&self, origins: Vec<String>) {
+        *self.allowed_origins.write() = origins;
     }
 
-    pub fn get_capture_delay_on_error(&self) -> u32 {
-        self.capture_delay_on_error.load(Ordering::Relaxed)
+    // Origin은 normalize_origin으로 정리한 뒤 비교한다.
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        let origin = normalize_origin(origin);
+        self.allowed_origins.read().iter().any(|o| normalize_origin(o) == origin)
     }
 
     // CVAT 관련 메서드
@@ -111,13 +293,21 @@ impl AppState {
         self.is_tracking.load(Ordering::Relaxed)
     }
 
-    pub fn get_instance(&self) -> parking_lot::RwLockReadGuard<'_, Option<cvAutoTrack>> {
-        self.instance.read()
+    pub fn get_instance(&self) -> Option<Arc<dyn TrackingBackend>> {
+        self.instance.read().clone()
     }
 
-    pub fn set_instance(&self, instance: Option<cvAutoTrack>) {
+    pub fn set_instance(&self, instance: Option<Arc<dyn TrackingBackend>>) {
         *self.instance.write() = instance;
     }
+
+    pub fn set_tracker_thread(&self, tracker_thread: JoinHandle<()>) {
+        *self.tracker_thread.lock() = Some(tracker_thread);
+    }
+
+    pub fn take_tracker_thread(&self) -> Option<JoinHandle<()>> {
+        self.tracker_thread.lock().take()
+    }
 }
 
 impl Default for AppState {
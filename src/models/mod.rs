@@ -1,6 +1,11 @@
 mod app;
+mod schema;
 mod track;
 mod ws;
 pub use self::app::*;
+pub use self::schema::*;
 pub use self::track::*;
 pub use self::ws::*;
+
+#[cfg(test)]
+mod test;
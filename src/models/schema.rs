@@ -0,0 +1,35 @@
+use schemars::gen::SchemaSettings;
+use serde_json::{json, Value};
+
+use super::{
+    AppConfig, DataTypes, Encoding, RequestDataTypes, RequestEvent, SendEvent, TrackData, UpdateInfo, WsEvent,
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+
+/*
+ * 외부 클라이언트(TypeScript 등)가 타입을 생성할 수 있도록, WebSocket 프로토콜에서
+ * 주고받는 타입들의 JSON Schema를 만든다.
+ * 모든 타입은 definitions 아래에 이름으로 들어가며, 서로 $ref로 참조한다.
+ * 실제로 주고받는 메세지는 클라이언트 -> GPA가 RequestEvent, GPA -> 클라이언트가 SendEvent이다.
+ */
+pub fn protocol_schema() -> Value {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    gen.subschema_for::<RequestEvent>();
+    gen.subschema_for::<RequestDataTypes>();
+    gen.subschema_for::<SendEvent>();
+    gen.subschema_for::<DataTypes>();
+    gen.subschema_for::<WsEvent>();
+    gen.subschema_for::<TrackData>();
+    gen.subschema_for::<AppConfig>();
+    gen.subschema_for::<UpdateInfo>();
+    gen.subschema_for::<Encoding>();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "GPA WebSocket Protocol",
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "protocolVersion": PROTOCOL_VERSION,
+        "minProtocolVersion": MIN_PROTOCOL_VERSION,
+        "definitions": gen.definitions(),
+    })
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod request_tests {
+    use crate::models::{RequestDataTypes, RequestEvent, Topic};
+    use serde_json::json;
+
+    fn parse(data: serde_json::Value) -> RequestEvent {
+        RequestEvent::from_value(json!({ "event": "test", "data": data, "requestId": "1" }))
+            .expect("request parse failed.")
+    }
+
+    #[test]
+    fn update_check() {
+        // checkAppUpdate와 checkLibUpdate는 data의 모양이 같다.
+        let req = parse(json!({ "force": true }));
+        assert!(matches!(req.data, Some(RequestDataTypes::CheckAppUpdate(_))));
+        assert!(req.data.unwrap().update_check().unwrap().force);
+    }
+
+    #[test]
+    fn topic_list() {
+        match parse(json!({ "topics": ["track", "config"] })).data {
+            Some(RequestDataTypes::TopicList(data)) => assert_eq!(data.topics, vec![Topic::Track, Topic::Config]),
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn log_topic() {
+        match parse(json!({ "topics": ["log"] })).data {
+            Some(RequestDataTypes::TopicList(data)) => assert_eq!(data.topics, vec![Topic::Log]),
+            other => panic!("unexpected data: {:?}", other),
+        }
+        // log는 구독한 클라이언트에게만 보낸다.
+        assert!(!Topic::DEFAULT.contains(&Topic::Log));
+    }
+
+    #[test]
+    fn hello() {
+        match parse(json!({ "protocolVersion": 2, "clientName": "map" })).data {
+            Some(RequestDataTypes::Hello(data)) => {
+                assert_eq!(data.protocol_version, 2);
+                assert_eq!(data.client_name.as_deref(), Some("map"));
+                assert!(data.client_version.is_none());
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ping() {
+        match parse(json!({ "clientTime": 1234 })).data {
+            Some(RequestDataTypes::Ping(data)) => assert_eq!(data.client_time, 1234),
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn app_config_keeps_raw_data() {
+        let req = parse(json!({ "capture_interval": 100 }));
+        match &req.data {
+            Some(RequestDataTypes::AppConfig(data)) => assert_eq!(data.capture_interval, 100),
+            other => panic!("unexpected data: {:?}", other),
+        }
+        assert_eq!(req.raw_data, Some(json!({ "capture_interval": 100 })));
+        assert_eq!(req.request_id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn no_data() {
+        let req = RequestEvent::from_value(json!({ "event": "getConfig" })).unwrap();
+        assert!(req.data.is_none());
+        assert!(req.raw_data.is_none());
+        assert!(req.request_id.is_none());
+    }
+}
+
+#[cfg(test)]
+mod publish_tests {
+    use crate::models::{AppConfig, WsEvent};
+    use serde_json::json;
+
+    fn track() -> serde_json::Value {
+        json!({ "x": 1.0, "y": 2.0, "a": 3.0, "r": 4.0, "m": 0, "err": "" })
+    }
+
+    #[test]
+    fn accepts_exact_data() {
+        assert!(matches!(WsEvent::from_published("track", Some(track())), Ok(WsEvent::Track { .. })));
+        assert!(matches!(WsEvent::from_published("uninit", None), Ok(WsEvent::Uninit)));
+        assert!(matches!(WsEvent::from_published("update", None), Ok(WsEvent::UpdateInfo { info: None })));
+    }
+
+    #[test]
+    fn rejects_unknown_event() {
+        assert!(WsEvent::from_published("paimon", None).is_err());
+        // 요청 이벤트는 클라이언트에 보낼 수 없다.
+        assert!(WsEvent::from_published("getConfig", None).is_err());
+        assert!(WsEvent::from_published("setConfig", Some(json!({}))).is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_data() {
+        let mut extra = track();
+        extra["script"] = json!("alert(1)");
+        let err = WsEvent::from_published("track", Some(extra)).unwrap_err();
+        assert!(err.contains("script"), "{}", err);
+
+        assert!(WsEvent::from_published("track", None).is_err());
+        assert!(WsEvent::from_published("track", Some(json!({ "x": "1" }))).is_err());
+        assert!(WsEvent::from_published("init", Some(track())).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        // 모든 항목이 선택 사항인 타입도 알 수 없는 항목은 거부한다.
+        assert!(WsEvent::from_published("trackDelta", Some(json!({ "x": 1.0 }))).is_ok());
+        let err = WsEvent::from_published("trackDelta", Some(json!({ "x": 1.0, "z": 2.0 }))).unwrap_err();
+        assert!(err.contains('z'), "{}", err);
+
+        // 안쪽 항목도 확인한다.
+        let mut nested = track();
+        nested["raw"] = json!({ "x": 1.0, "y": 2.0, "a": 3.0, "r": 4.0, "script": "alert(1)" });
+        let err = WsEvent::from_published("track", Some(nested)).unwrap_err();
+        assert!(err.contains("raw.script"), "{}", err);
+
+        let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+        assert!(WsEvent::from_published("config", Some(config.clone())).is_ok());
+        config["paimon"] = json!(true);
+        let err = WsEvent::from_published("config", Some(config)).unwrap_err();
+        assert!(err.contains("paimon"), "{}", err);
+
+        let log = json!({ "level": "info", "target": "gpa", "message": "paimon", "time": 0, "html": "<b>" });
+        assert!(WsEvent::from_published("log", Some(log)).is_err());
+    }
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use crate::models::{protocol_schema, AppConfig, SendEvent, TrackData, WsEvent};
+    use jsonschema::{Draft, JSONSchema};
+    use serde_json::json;
+
+    // definitions 중 하나를 루트로 하는 검증기. 스키마가 draft-07 메타 스키마에 맞지 않으면 실패한다.
+    fn compile(definition: &str) -> JSONSchema {
+        let mut schema = protocol_schema();
+        schema["$ref"] = json!(format!("#/definitions/{}", definition));
+        JSONSchema::options()
+            .with_draft(Draft::Draft7)
+            .compile(&schema)
+            .unwrap_or_else(|e| panic!("invalid schema for {}: {}", definition, e))
+    }
+
+    #[test]
+    fn schema_is_valid_draft7() {
+        let schema = protocol_schema();
+        for definition in ["RequestEvent", "RequestDataTypes", "SendEvent", "DataTypes", "WsEvent", "TrackData", "AppConfig", "UpdateInfo", "Encoding"] {
+            assert!(schema["definitions"].get(definition).is_some(), "{} is missing", definition);
+            compile(definition);
+        }
+    }
+
+    #[test]
+    fn messages_match_schema() {
+        let send_event = compile("SendEvent");
+        for event in [
+            SendEvent::from(WsEvent::Track { data: TrackData { x: 1.0, ..Default::default() } }),
+            SendEvent::from(WsEvent::Config { config: AppConfig::default(), id: "1".to_string() }),
+            SendEvent::from(WsEvent::DoneInit),
+        ] {
+            let value = serde_json::to_value(&event).unwrap();
+            assert!(send_event.is_valid(&value), "{} does not match the schema", value);
+        }
+        assert!(!send_event.is_valid(&json!({ "data": null })));
+
+        let request_event = compile("RequestEvent");
+        assert!(request_event.is_valid(&json!({ "event": "subscribe", "data": { "topics": ["track"] }, "requestId": "1" })));
+        assert!(!request_event.is_valid(&json!({ "data": { "topics": ["track"] } })));
+    }
+}
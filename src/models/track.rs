@@ -1,14 +1,19 @@
 use libc::{c_double, c_int};
 pub use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
 pub struct TrackData {
     pub x: c_double,
     pub y: c_double,
     pub a: c_double,
     pub r: c_double,
     pub m: c_int,
+    #[serde(default)]
     pub err: String,
+    // 필터가 켜져있을 때, 필터를 거치기 전의 원본 값
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<RawTrackData>,
 }
 
 impl Clone for TrackData {
@@ -20,14 +25,50 @@ impl Clone for TrackData {
             r: self.r,
             m: self.m,
             err: self.err.clone(),
+            raw: self.raw,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(untagged)]
-#[derive(Debug, Copy, Clone)]
-pub enum CaptureMode {
-    DirectX,
-    Bitblt,
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, Copy, PartialEq)]
+pub struct RawTrackData {
+    pub x: c_double,
+    pub y: c_double,
+    pub a: c_double,
+    pub r: c_double,
+}
+
+// 변경된 항목만 담아 보내는 TrackData
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct TrackDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<c_double>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<c_double>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a: Option<c_double>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<c_double>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m: Option<c_int>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub err: Option<String>,
+}
+
+// 기록/재생 파일의 한 줄에 해당하는 샘플
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TrackSample {
+    // 기록 시작 시점부터의 경과 시간 (ms)
+    pub t: u64,
+    #[serde(flatten)]
+    pub data: TrackData,
+}
+
+// 추적 데이터 기록 상태
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub recording: bool,
+    pub file: String,
+    pub samples: u64,
 }
@@ -1,17 +1,118 @@
 pub use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use serde_variant::to_variant_name;
 
-use super::{AppConfig, AppInfo, TrackData, UpdateInfo};
+use super::{AppConfig, AppInfo, RecordingInfo, TrackData, TrackDelta, UpdateInfo};
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(bound(deserialize = "'de: 'static"))]
+// GPA가 사용하는 WebSocket 프로토콜 버전.
+// 1: hello 이전의 프로토콜 (hello를 보내지 않는 클라이언트는 1로 취급한다)
+// 2: hello, requestId, error, subscribe/unsubscribe, trackDelta
+pub const PROTOCOL_VERSION: u32 = 2;
+// 지원하는 가장 낮은 클라이언트 프로토콜 버전
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct SendEvent {
     pub event: String,
     pub data: Option<DataTypes>,
+    // 요청에 requestId가 있었다면, 그 요청에 대한 응답에 그대로 돌려준다.
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // broadcast 시 이 topic을 구독한 클라이언트에게만 보낸다. None이면 모두에게 보낸다.
+    #[serde(skip)]
+    pub topic: Option<Topic>,
+}
+
+// GPA가 클라이언트에게 보내는 메세지의 인코딩. 등록 시 선택한다.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Encoding {
+    // text 프레임의 JSON
+    #[default]
+    Json,
+    // binary 프레임의 MessagePack. 필드 이름은 JSON과 같다.
+    Msgpack,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCode {
+    // 요청을 해석할 수 없음
+    InvalidRequest,
+    // 처리할 수 있는 핸들러가 없는 이벤트
+    UnknownEvent,
+    // 핸들러 처리 중 실패
+    HandlerFailed,
+    // 지원하지 않는 프로토콜 버전
+    IncompatibleProtocol,
+}
+
+// 접속 직후 및 hello 요청에 대한 응답으로 GPA가 보내는 정보
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HelloInfo {
+    pub app_version: String,
+    pub protocol_version: u32,
+    pub min_protocol_version: u32,
+    pub lib_version: String,
+    pub events: Vec<String>,
+    // 클라이언트의 hello를 받은 뒤 결정된 프로토콜 버전
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negotiated_version: Option<u32>,
+}
+
+// ping 요청에 대한 응답. 시간은 모두 UNIX epoch 기준 ms
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PongInfo {
+    pub server_time: u64,
+    // ping 요청에 담겨온 클라이언트 시간. 왕복 지연 계산에 사용한다.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_time: Option<u64>,
+}
+
+// GPA가 남긴 로그 한 줄. 시간은 UNIX epoch 기준 ms
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogInfo {
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(bound(deserialize = "'de: 'static"))]
+// 클라이언트가 구독할 수 있는 broadcast 분류
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[serde(rename_all = "camelCase")]
+pub enum Topic {
+    Track,
+    Update,
+    Config,
+    Log,
+    Status,
+}
+
+impl Topic {
+    // 따로 구독하지 않은 클라이언트가 받는 topic. log는 양이 많으므로 구독한 클라이언트에게만 보낸다.
+    pub const DEFAULT: [Topic; 4] = [Topic::Track, Topic::Update, Topic::Config, Topic::Status];
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicList {
+    pub topics: Vec<Topic>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 #[derive(Debug, Clone)]
 pub enum DataTypes {
@@ -19,10 +120,16 @@ pub enum DataTypes {
     AppInfo(AppInfo),
     AppConfig(AppConfig),
     UpdateInfo(UpdateInfo),
+    RecordingInfo(RecordingInfo),
+    TrackDelta(TrackDelta),
+    TopicList(TopicList),
+    ErrorInfo(ErrorInfo),
+    HelloInfo(HelloInfo),
+    PongInfo(PongInfo),
+    LogInfo(LogInfo),
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(bound(deserialize = "'de: 'static"))]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[derive(Debug, Clone)]
 pub enum WsEvent {
@@ -33,10 +140,82 @@ pub enum WsEvent {
     DoneInit,
     Uninit,
     Track { data: TrackData },
+    TrackDelta { data: TrackDelta },
     CheckLibUpdate { id: String },
     CheckAppUpdate { id: String },
     #[serde(rename = "update")]
     UpdateInfo { info: Option<UpdateInfo> },
+    Recording { info: RecordingInfo },
+    Subscriptions { topics: TopicList },
+    Error { info: ErrorInfo },
+    Hello { info: HelloInfo },
+    Pong { info: PongInfo },
+    Log { info: LogInfo },
+}
+
+impl WsEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            WsEvent::Track { .. } | WsEvent::TrackDelta { .. } => Topic::Track,
+            WsEvent::CheckLibUpdate { .. } | WsEvent::CheckAppUpdate { .. } | WsEvent::UpdateInfo { .. } => Topic::Update,
+            WsEvent::GetConfig | WsEvent::SetConfig { .. } | WsEvent::Config { .. } => Topic::Config,
+            WsEvent::Log { .. } => Topic::Log,
+            WsEvent::Init | WsEvent::DoneInit | WsEvent::Uninit
+                | WsEvent::Recording { .. } | WsEvent::Subscriptions { .. } | WsEvent::Error { .. }
+                | WsEvent::Hello { .. } | WsEvent::Pong { .. } => Topic::Status,
+        }
+    }
+}
+
+impl WsEvent {
+    /*
+     * POST /publish로 받은 이벤트를 해석한다.
+     * event는 GPA가 클라이언트에 보내는 이벤트 이름이어야 하고 (getConfig 같은 요청 이벤트는 안 된다),
+     * data는 그 이벤트의 타입과 정확히 맞아야 한다. 알 수 없는 항목이 있으면 실패한다.
+     */
+    pub fn from_published(event: &str, data: Option<serde_json::Value>) -> Result<WsEvent, String> {
+        let event = match event {
+            "config" => WsEvent::Config { config: strict_data(event, data)?, id: String::new() },
+            "init" => no_data(event, data, WsEvent::Init)?,
+            "doneInit" => no_data(event, data, WsEvent::DoneInit)?,
+            "uninit" => no_data(event, data, WsEvent::Uninit)?,
+            "track" => WsEvent::Track { data: strict_data(event, data)? },
+            "trackDelta" => WsEvent::TrackDelta { data: strict_data(event, data)? },
+            "update" => WsEvent::UpdateInfo {
+                info: match data {
+                    None | Some(serde_json::Value::Null) => None,
+                    data => Some(strict_data(event, data)?),
+                },
+            },
+            "recording" => WsEvent::Recording { info: strict_data(event, data)? },
+            "subscriptions" => WsEvent::Subscriptions { topics: strict_data(event, data)? },
+            "error" => WsEvent::Error { info: strict_data(event, data)? },
+            "hello" => WsEvent::Hello { info: strict_data(event, data)? },
+            "pong" => WsEvent::Pong { info: strict_data(event, data)? },
+            "log" => WsEvent::Log { info: strict_data(event, data)? },
+            _ => return Err(format!("publish할 수 없는 이벤트입니다: {}", event)),
+        };
+        Ok(event)
+    }
+}
+
+fn strict_data<T: serde::de::DeserializeOwned>(event: &str, data: Option<serde_json::Value>) -> Result<T, String> {
+    let data = data.ok_or_else(|| format!("{} 이벤트에는 data가 필요합니다.", event))?;
+    let mut unknown = Vec::new();
+    // Option 안의 항목은 경로에 ?가 붙으므로 지운다. (raw.?.x -> raw.x)
+    let value = serde_ignored::deserialize(data, |path| unknown.push(path.to_string().replace(".?", "")))
+        .map_err(|e| format!("{} 이벤트의 data가 올바르지 않습니다: {}", event, e))?;
+    if !unknown.is_empty() {
+        return Err(format!("{} 이벤트의 data에 알 수 없는 항목이 있습니다: {}", event, unknown.join(", ")));
+    }
+    Ok(value)
+}
+
+fn no_data(event: &str, data: Option<serde_json::Value>, ws_event: WsEvent) -> Result<WsEvent, String> {
+    match data {
+        None | Some(serde_json::Value::Null) => Ok(ws_event),
+        Some(_) => Err(format!("{} 이벤트에는 data가 없어야 합니다.", event)),
+    }
 }
 
 impl From<WsEvent> for SendEvent {
@@ -44,41 +223,95 @@ impl From<WsEvent> for SendEvent {
         let event_name = to_variant_name(&event).unwrap();
         let data = match &event {
             WsEvent::Track { data } => Some(DataTypes::TrackData(data.clone())),
+            WsEvent::TrackDelta { data } => Some(DataTypes::TrackDelta(data.clone())),
             WsEvent::Config { config, id: _ } => Some(DataTypes::AppConfig(config.clone())),
             WsEvent::UpdateInfo { info } if info.is_some() => {
                 Some(DataTypes::UpdateInfo(info.clone().unwrap()))
             },
+            WsEvent::Recording { info } => Some(DataTypes::RecordingInfo(info.clone())),
+            WsEvent::Subscriptions { topics } => Some(DataTypes::TopicList(topics.clone())),
+            WsEvent::Error { info } => Some(DataTypes::ErrorInfo(info.clone())),
+            WsEvent::Hello { info } => Some(DataTypes::HelloInfo(info.clone())),
+            WsEvent::Pong { info } => Some(DataTypes::PongInfo(info.clone())),
+            WsEvent::Log { info } => Some(DataTypes::LogInfo(info.clone())),
             _ => None
         };
         
         SendEvent {
             event: event_name.to_string(),
-            data
+            data,
+            request_id: None,
+            topic: Some(event.topic()),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[derive(Debug, Clone)]
 pub struct RequestEvent {
     pub event: String,
     pub data: Option<RequestDataTypes>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    // 해석하기 전의 data. setConfig처럼 요청에 실제로 들어있던 항목만 골라 써야 할 때 사용한다.
+    #[serde(skip)]
+    pub raw_data: Option<serde_json::Value>,
+}
+
+impl RequestEvent {
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let mut req = RequestEvent::deserialize(&value)?;
+        req.raw_data = value.get("data").cloned();
+        Ok(req)
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]
 #[derive(Debug, Clone)]
 pub enum RequestDataTypes {
-    AppConfig(AppConfig),
     CheckAppUpdate(RequestUpdateCheck),
     CheckLibUpdate(RequestUpdateCheck),
+    TopicList(TopicList),
+    Hello(ClientHello),
+    Ping(ClientPing),
+    // AppConfig는 모든 항목에 기본값이 있어 어떤 객체든 받아들이므로 항상 마지막에 둔다.
+    AppConfig(Box<AppConfig>),
 }
 
-#[derive(Serialize, Deserialize)]
+impl RequestDataTypes {
+    // checkAppUpdate와 checkLibUpdate의 data는 모양이 같아서 항상 CheckAppUpdate로 해석된다.
+    pub fn update_check(&self) -> Option<&RequestUpdateCheck> {
+        match self {
+            RequestDataTypes::CheckAppUpdate(data) | RequestDataTypes::CheckLibUpdate(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[derive(Debug, Clone)]
 pub struct RequestUpdateCheck {
     pub force: bool,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+pub struct ClientHello {
+    pub protocol_version: u32,
+    #[serde(default)]
+    pub client_name: Option<String>,
+    #[serde(default)]
+    pub client_version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+pub struct ClientPing {
+    pub client_time: u64,
+}
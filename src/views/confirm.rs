@@ -2,15 +2,11 @@ pub fn confirm_dialog(title:&str, desc: &str, is_error: bool) -> std::option::Op
     #[cfg(any(
         target_os = "windows",
         target_os = "macos",
-        all(
-            any(
-                target_os = "linux",
-                target_os = "freebsd",
-                target_os = "dragonfly",
-                target_os = "netbsd",
-                target_os = "openbsd"
-            ),
-        )
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
     ))]
     let res = rfd::MessageDialog::new()
         .set_title(title)
@@ -19,6 +15,18 @@ pub fn confirm_dialog(title:&str, desc: &str, is_error: bool) -> std::option::Op
         .set_level(if is_error{rfd::MessageLevel::Error}else{rfd::MessageLevel::Info})
         .show();
 
-    return Some(res);
+    Some(res)
 
-}
\ No newline at end of file
+}
+
+// 예/아니오를 묻는 대화상자. 예를 선택한 경우에만 true
+pub fn ask_dialog(title: &str, desc: &str) -> bool {
+    let res = rfd::MessageDialog::new()
+        .set_title(title)
+        .set_description(desc)
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .set_level(rfd::MessageLevel::Warning)
+        .show();
+
+    res == rfd::MessageDialogResult::Yes
+}
@@ -0,0 +1,177 @@
+use super::origin::normalize_origin;
+use crate::app::path::get_pairings_path;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::SystemTime;
+use uuid::Uuid;
+use warp::{Filter, Rejection};
+
+static PAIRINGS: Lazy<PairingStore> = Lazy::new(|| PairingStore::load(get_pairings_path()));
+
+/*
+ * 페어링된 클라이언트 목록.
+ * 사용자가 허용한 클라이언트에게만 GPA가 생성한 토큰을 발급하고,
+ * register/publish/ws 요청은 이 토큰을 제시해야 한다.
+ * 목록은 앱 디렉토리의 pairings.json에 저장된다.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Pairing {
+    // 브라우저에서 페어링한 경우 요청의 Origin. 이후 요청도 같은 Origin에서 와야 한다.
+    pub origin: Option<String>,
+    // Origin이 없는 클라이언트(로컬 프로그램 등)가 밝힌 이름
+    pub name: Option<String>,
+    pub token: String,
+    pub paired_at: String,
+}
+
+impl Pairing {
+    pub fn label(&self) -> String {
+        self.origin.clone()
+            .or_else(|| self.name.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub struct PairingStore {
+    path: PathBuf,
+    pairings: RwLock<Vec<Pairing>>,
+}
+
+// 토큰이 없거나 유효하지 않은 요청
+#[derive(Debug)]
+pub struct Unauthorized;
+impl warp::reject::Reject for Unauthorized {}
+
+// 사용자가 페어링을 거부한 요청
+#[derive(Debug)]
+pub struct PairingDenied;
+impl warp::reject::Reject for PairingDenied {}
+
+impl PairingStore {
+    pub fn global() -> &'static PairingStore {
+        &PAIRINGS
+    }
+
+    pub(super) fn load(path: PathBuf) -> Self {
+        let pairings = match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+                log::error!("Pairings: 로드 실패");
+                log::error!("Error: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            path,
+            pairings: RwLock::new(pairings),
+        }
+    }
+
+    fn save(&self, pairings: &[Pairing]) -> std::io::Result<()> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(pairings)?)
+    }
+
+    // 새 토큰을 발급한다. 같은 클라이언트의 기존 토큰은 폐기된다.
+    pub fn pair(&self, origin: Option<String>, name: Option<String>) -> std::io::Result<String> {
+        let token = format!("{}{}", Uuid::new_v4().as_simple(), Uuid::new_v4().as_simple());
+        let pairing = Pairing {
+            origin: origin.map(|o| normalize_origin(&o)),
+            name,
+            token: token.clone(),
+            paired_at: chrono::DateTime::<chrono::Utc>::from(SystemTime::now()).to_rfc3339(),
+        };
+        let mut pairings = self.pairings.write();
+        pairings.retain(|p| p.label() != pairing.label());
+        log::debug!("Pairings: {} 페어링", pairing.label());
+        pairings.push(pairing);
+        self.save(&pairings)?;
+        Ok(token)
+    }
+
+    // 토큰과 함께 페어링한 Origin도 확인한다. Origin은 normalize_origin으로 정리한 뒤 비교한다.
+    pub fn verify(&self, token: &str, origin: Option<&str>) -> bool {
+        let origin = origin.map(normalize_origin);
+        self.pairings.read().iter().any(|p| {
+            constant_time_eq(p.token.as_bytes(), token.as_bytes())
+                && p.origin.as_deref().map(normalize_origin) == origin
+        })
+    }
+
+    pub fn find(&self, token: &str) -> Option<Pairing> {
+        self.pairings.read().iter()
+            .find(|p| constant_time_eq(p.token.as_bytes(), token.as_bytes()))
+            .cloned()
+    }
+
+    pub fn list(&self) -> Vec<Pairing> {
+        self.pairings.read().clone()
+    }
+
+    // 토큰에 해당하는 페어링을 해제한다.
+    pub fn revoke(&self, token: &str) -> std::io::Result<bool> {
+        let mut pairings = self.pairings.write();
+        let len = pairings.len();
+        pairings.retain(|p| !constant_time_eq(p.token.as_bytes(), token.as_bytes()));
+        if pairings.len() == len {
+            return Ok(false);
+        }
+        self.save(&pairings)?;
+        Ok(true)
+    }
+
+    pub fn revoke_all(&self) -> std::io::Result<()> {
+        let mut pairings = self.pairings.write();
+        pairings.clear();
+        self.save(&pairings)
+    }
+}
+
+// 토큰 비교에 걸리는 시간으로 토큰을 추측할 수 없도록 한다.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn check_token(token: Option<String>, origin: Option<String>) -> Result<(), Rejection> {
+    match token {
+        Some(token) if PairingStore::global().verify(&token, origin.as_deref()) => Ok(()),
+        _ => {
+            log::error!("인증되지 않은 요청 (origin: {:?})", origin);
+            Err(warp::reject::custom(Unauthorized))
+        }
+    }
+}
+
+// Authorization: Bearer <token> 헤더를 요구하는 필터. 통과하면 토큰을 넘겨준다.
+pub fn with_token() -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(warp::header::optional::<String>("origin"))
+        .and_then(|auth: Option<String>, origin: Option<String>| async move {
+            let token = auth.as_deref()
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .map(|t| t.trim().to_string());
+            check_token(token.clone(), origin)?;
+            Ok::<_, Rejection>(token.unwrap_or_default())
+        })
+}
+
+pub fn with_auth() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    with_token().map(|_| ()).untuple_one()
+}
+
+// 브라우저의 WebSocket은 헤더를 지정할 수 없으므로, /ws/{id}?token=<token> 으로 토큰을 받는다.
+pub fn with_query_auth() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::query::raw()
+        .or(warp::any().map(String::new))
+        .unify()
+        .and(warp::header::optional::<String>("origin"))
+        .and_then(|query: String, origin: Option<String>| async move {
+            let token = query.split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+                .map(|t| t.to_string());
+            check_token(token, origin)
+        })
+        .untuple_one()
+}
@@ -1,21 +1,88 @@
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-use crate::websocket::{Client, Clients};
+use crate::app::config::ConfigManager;
+use crate::app::path;
+use crate::models::{protocol_schema, Encoding, SendEvent, Topic, WsEvent};
+use crate::views::confirm::ask_dialog;
+use crate::websocket::{Client, ClientStats, Clients, QueuePolicy};
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
-use warp::{http::StatusCode, reply::json, ws::Message, Reply};
+use warp::{http::StatusCode, reply::json, Reply};
 
-use super::ws::client_connection;
+use super::auth::{PairingDenied, PairingStore, Unauthorized};
+use super::origin::OriginRejected;
+use super::ws::{client_connection, encode};
 use super::WebSocketHandler;
 
 #[derive(Deserialize, Debug)]
 pub struct RegisterRequest {
     user_id: usize,
+    // 클라이언트 이름, 종류(userscript, extension 등), 버전
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    // GPA가 보낼 메세지의 인코딩. 없으면 json
+    #[serde(default)]
+    encoding: Encoding,
 }
 
 #[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct RegisterResponse {
+    id: String,
     url: String,
+    // 이 시간(초) 안에 url로 연결하지 않으면 등록이 해제된다.
+    expires_in: u32,
+    encoding: Encoding,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfo {
+    id: String,
+    user_id: usize,
+    name: Option<String>,
+    kind: Option<String>,
+    version: Option<String>,
+    connected: bool,
+    registered_at: String,
+    connected_at: Option<String>,
+    received: u64,
+    sent: u64,
+    dropped: u64,
+    queued: usize,
+    protocol_version: u32,
+    encoding: Encoding,
+    topics: Vec<Topic>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PairRequest {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PairResponse {
+    token: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HealthResponse {
+    status: String,
+    version: String,
+    address: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -25,7 +92,91 @@ pub struct Event {
     message: String,
 }
 
-pub async fn publish_handler(body: Event, clients: Clients) -> Result<impl Reply, warp::Rejection> {
+// publish의 message. SendEvent 형식이지만 requestId 같은 다른 항목은 받지 않는다.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PublishMessage {
+    event: String,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+// 감사 기록 파일의 최대 크기. 넘으면 publish-audit.jsonl.1로 옮기고 새로 쓴다.
+pub(super) const PUBLISH_AUDIT_MAX_SIZE: u64 = 1024 * 1024;
+
+// publish 요청 감사 기록. publish-audit.jsonl에 한 줄씩 추가된다.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PublishAudit {
+    time: String,
+    publisher: String,
+    user_id: Option<usize>,
+    event: Option<String>,
+    delivered: usize,
+    error: Option<String>,
+}
+
+fn write_publish_audit(audit: &PublishAudit) {
+    let result = serde_json::to_string(audit).map_err(std::io::Error::from).and_then(|mut line| {
+        line.push('\n');
+        rotate_publish_audit(&path::get_publish_audit_path(), line.len() as u64)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path::get_publish_audit_path())?
+            .write_all(line.as_bytes())
+    });
+    if let Err(e) = result {
+        log::error!("Publish Audit: 기록 실패");
+        log::error!("Error: {}", e);
+    }
+}
+
+// incoming을 더하면 PUBLISH_AUDIT_MAX_SIZE를 넘는 경우 기존 기록을 <파일명>.1로 옮긴다. 이전 .1은 덮어쓴다.
+pub(super) fn rotate_publish_audit(path: &Path, incoming: u64) -> std::io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.len() > 0 && meta.len() + incoming > PUBLISH_AUDIT_MAX_SIZE => {
+            let mut backup = path.to_path_buf().into_os_string();
+            backup.push(".1");
+            std::fs::rename(path, backup)
+        }
+        _ => Ok(()),
+    }
+}
+
+/*
+ * 페어링된 클라이언트가 다른 클라이언트들에게 이벤트를 보낸다.
+ * publish_enabled가 꺼져있으면 사용할 수 없고,
+ * message는 {event, data} 형식이어야 한다. event는 WsEvent의 이름이고, data는 그 이벤트의 타입과 정확히 맞아야 한다.
+ * 해석된 이벤트를 다시 직렬화해서 보낸다.
+ */
+pub async fn publish_handler(token: String, body: Event, clients: Clients) -> Result<impl Reply, warp::Rejection> {
+    let publisher = PairingStore::global().find(&token).map(|p| p.label()).unwrap_or_default();
+    let mut audit = PublishAudit {
+        time: chrono::DateTime::<chrono::Utc>::from(SystemTime::now()).to_rfc3339(),
+        publisher,
+        user_id: body.user_id,
+        event: None,
+        delivered: 0,
+        error: None,
+    };
+
+    if !ConfigManager::global().get().await.publish_enabled {
+        audit.error = Some(String::from("publish disabled"));
+        write_publish_audit(&audit);
+        return Ok(StatusCode::FORBIDDEN);
+    }
+
+    let event = match parse_published(&body.message, &mut audit) {
+        Ok(event) => event,
+        Err(e) => {
+            log::error!("잘못된 publish 요청 ({}): {}", audit.publisher, e);
+            audit.error = Some(e.to_string());
+            write_publish_audit(&audit);
+            return Ok(StatusCode::BAD_REQUEST);
+        }
+    };
+
     clients
         .read()
         .await
@@ -34,36 +185,98 @@ pub async fn publish_handler(body: Event, clients: Clients) -> Result<impl Reply
             Some(v) => client.user_id == v,
             None => true,
         })
-        .for_each(|(_, client)| {
-            if let Some(sender) = &client.sender {
-                let _ = sender.send(Ok(Message::text(body.message.clone())));
+        .for_each(|(id, client)| {
+            if client.sender.is_none() {
+                return;
+            }
+            match encode(&event, client.encoding) {
+                Ok(message) => {
+                    if client.send(message, QueuePolicy::Reliable).is_ok() {
+                        audit.delivered += 1;
+                    }
+                }
+                Err(e) => log::error!("publish to {} failed: {}", id, e),
             }
         });
 
+    log::debug!("publish: {:?}", audit);
+    write_publish_audit(&audit);
     Ok(StatusCode::OK)
 }
 
-pub async fn register_handler(body: RegisterRequest, clients: Clients) -> Result<impl Reply, warp::Rejection> {
-    let user_id = body.user_id;
+fn parse_published(message: &str, audit: &mut PublishAudit) -> Result<SendEvent, String> {
+    let message = serde_json::from_str::<PublishMessage>(message).map_err(|e| e.to_string())?;
+    audit.event = Some(message.event.clone());
+    WsEvent::from_published(&message.event, message.data).map(SendEvent::from)
+}
+
+pub async fn register_handler(
+    body: RegisterRequest,
+    clients: Clients,
+    ws_handler: Arc<WebSocketHandler>,
+) -> Result<impl Reply, warp::Rejection> {
     let uuid = Uuid::new_v4().as_simple().to_string();
+    log::debug!("register {}: {:?}", uuid, body);
 
-    register_client(uuid.clone(), user_id, clients).await;
+    let encoding = body.encoding;
+    register_client(uuid.clone(), body, clients).await;
 
+    // 서버가 bind된 뒤에만 라우트가 동작하므로 주소는 항상 존재한다.
+    let url = ws_handler.ws_url(&uuid).await.unwrap_or_default();
     Ok(json(&RegisterResponse {
-        url: format!("ws://127.0.0.1:32332/ws/{}", uuid),
+        id: uuid,
+        url,
+        expires_in: ConfigManager::global().get().await.register_ttl,
+        encoding,
     }))
 }
 
-async fn register_client(id: String, user_id: usize, clients: Clients) {
+async fn register_client(id: String, body: RegisterRequest, clients: Clients) {
     clients.write().await.insert(
         id,
         Client {
-            user_id,
+            user_id: body.user_id,
+            name: body.name,
+            kind: body.kind,
+            version: body.version,
+            registered_at: SystemTime::now(),
+            connected_at: None,
+            disconnected_at: None,
+            stats: Arc::new(ClientStats::default()),
             sender: None,
+            topics: Topic::DEFAULT.into_iter().collect(),
+            protocol_version: 1,
+            encoding: body.encoding,
         },
     );
 }
 
+pub async fn clients_handler(clients: Clients) -> Result<impl Reply, warp::Rejection> {
+    let format_time = |t: SystemTime| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339();
+    let mut list: Vec<ClientInfo> = clients.read().await
+        .iter()
+        .map(|(id, c)| ClientInfo {
+            id: id.clone(),
+            user_id: c.user_id,
+            name: c.name.clone(),
+            kind: c.kind.clone(),
+            version: c.version.clone(),
+            connected: c.sender.is_some(),
+            registered_at: format_time(c.registered_at),
+            connected_at: c.connected_at.map(format_time),
+            received: c.stats.received.load(Ordering::Relaxed),
+            sent: c.stats.sent.load(Ordering::Relaxed),
+            dropped: c.stats.dropped.load(Ordering::Relaxed),
+            queued: c.sender.as_ref().map_or(0, |q| q.queued()),
+            protocol_version: c.protocol_version,
+            encoding: c.encoding,
+            topics: c.topics.iter().copied().collect(),
+        })
+        .collect();
+    list.sort_by(|a, b| a.registered_at.cmp(&b.registered_at));
+    Ok(json(&list))
+}
+
 pub async fn unregister_handler(id: String, clients: Clients) -> Result<impl Reply, warp::Rejection> {
     clients.write().await.remove(&id);
     Ok(StatusCode::OK)
@@ -84,6 +297,109 @@ pub async fn ws_handler(
     }
 }
 
-pub async fn health_handler() -> Result<impl Reply, warp::Rejection> {
-    Ok(StatusCode::OK)
-}
\ No newline at end of file
+pub async fn health_handler(ws_handler: Arc<WebSocketHandler>) -> Result<impl Reply, warp::Rejection> {
+    let address = ws_handler.local_addr().await.map(|addr| addr.to_string()).unwrap_or_default();
+    Ok(json(&HealthResponse {
+        status: String::from("ok"),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        address,
+    }))
+}
+
+pub async fn schema_handler() -> Result<impl Reply, warp::Rejection> {
+    Ok(json(&protocol_schema()))
+}
+
+// 사용자에게 페어링 허용 여부를 묻고, 허용하면 토큰을 발급한다.
+pub async fn pair_handler(origin: Option<String>, body: PairRequest) -> Result<impl Reply, warp::Rejection> {
+    let label = match origin.clone().or_else(|| body.name.clone()) {
+        Some(label) => label,
+        None => return Err(warp::reject::custom(PairingDenied)),
+    };
+    log::debug!("Pairing 요청: {}", label);
+
+    let desc = format!("'{}'에서 GPA 연결을 요청했습니다.\n허용하면 이 사이트(프로그램)가 GPA를 제어할 수 있습니다.\n허용하시겠습니까?", label);
+    let allowed = tokio::task::spawn_blocking(move || ask_dialog(env!("CARGO_PKG_DESCRIPTION"), &desc))
+        .await
+        .unwrap_or(false);
+    if !allowed {
+        log::debug!("Pairing 거부: {}", label);
+        return Err(warp::reject::custom(PairingDenied));
+    }
+
+    match PairingStore::global().pair(origin, body.name) {
+        Ok(token) => Ok(json(&PairResponse { token })),
+        Err(e) => {
+            log::error!("Pairings: 저장 실패");
+            log::error!("Error: {}", e);
+            Err(warp::reject::reject())
+        }
+    }
+}
+
+// 요청에 사용된 토큰의 페어링을 해제한다.
+pub async fn unpair_handler(token: String) -> Result<impl Reply, warp::Rejection> {
+    match PairingStore::global().revoke(&token) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => {
+            log::error!("Pairings: 저장 실패");
+            log::error!("Error: {}", e);
+            Err(warp::reject::reject())
+        }
+    }
+}
+
+// 인증 관련 거부를 HTTP 상태 코드로 바꾼다. 나머지는 warp의 기본 처리를 따른다.
+pub async fn handle_rejection(err: warp::Rejection) -> Result<impl Reply, warp::Rejection> {
+    if err.find::<Unauthorized>().is_some() {
+        Ok(StatusCode::UNAUTHORIZED)
+    } else if err.find::<PairingDenied>().is_some() || err.find::<OriginRejected>().is_some() {
+        Ok(StatusCode::FORBIDDEN)
+    } else {
+        Err(err)
+    }
+}
+
+// GET /events?topics=track,update : broadcast되는 이벤트를 Server-Sent Events로 보낸다. topics가 없으면 Topic::DEFAULT
+pub async fn events_handler(query: String, ws_handler: Arc<WebSocketHandler>) -> Result<impl Reply, warp::Rejection> {
+    let topics: Option<BTreeSet<Topic>> = query.split('&')
+        .find_map(|pair| pair.strip_prefix("topics="))
+        .map(|list| list.split(',')
+            .filter_map(|t| serde_json::from_value(serde_json::Value::String(t.to_string())).ok())
+            .collect());
+    log::debug!("SSE subscribed: {:?}", topics);
+
+    let rx = ws_handler.subscribe_events();
+    let stream = futures::stream::unfold(rx, move |mut rx| {
+        let topics = topics.clone();
+        async move {
+            loop {
+                match rx.recv().await {
+                    Ok(e) => {
+                        let subscribed = match (&topics, e.topic) {
+                            (Some(topics), Some(topic)) => topics.contains(&topic),
+                            (None, Some(topic)) => Topic::DEFAULT.contains(&topic),
+                            _ => true,
+                        };
+                        if subscribed {
+                            let event = warp::sse::Event::default().event(e.event).data(e.json);
+                            return Some((Ok::<_, Infallible>(event), rx));
+                        }
+                    }
+                    // 느린 구독자는 밀린 이벤트를 건너뛴다.
+                    Err(RecvError::Lagged(n)) => log::debug!("SSE lagged: {} events skipped", n),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
+}
+
+// GET /track/latest : 가장 최근의 추적 결과. 추적 중이 아니라면 204
+pub async fn latest_track_handler(ws_handler: Arc<WebSocketHandler>) -> Result<impl Reply, warp::Rejection> {
+    match ws_handler.latest_track() {
+        Some(data) => Ok(json(&data).into_response()),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
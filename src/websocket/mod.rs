@@ -1,14 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::convert::Infallible;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use warp::http::Method;
-use warp::{ws::Message, Filter};
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+use warp::reply::Response;
+use warp::{ws::Message, Filter, Rejection};
 use std::error::Error;
 
+mod auth;
+mod origin;
+mod queue;
 mod ws;
 mod handler;
 
+use crate::app::config::ConfigManager;
+use crate::app::logger;
+use crate::events::EventBus;
+use crate::models::{Encoding, ErrorCode, PongInfo, RequestDataTypes, RequestEvent, SendEvent, Topic, WsEvent, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+pub use auth::PairingStore;
+pub use origin::normalize_origin;
+pub use queue::{QueueClosed, QueuePolicy, SendQueue};
 pub use ws::WebSocketHandler;
 /*
  * Client를 Hash맵에 저장해 track하여 연결 유지
@@ -23,64 +37,289 @@ type Clients = Arc<RwLock<HashMap<String, Client>>>; // Arc<RwLock<HashMap<Strin
 #[derive(Debug, Clone)]
 pub struct Client {
     pub user_id: usize,
-    pub sender: Option<mpsc::UnboundedSender<std::result::Result<Message, warp::Error>>>,
+    // 등록 시 클라이언트가 밝힌 정보
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    pub version: Option<String>,
+    pub registered_at: SystemTime,
+    pub connected_at: Option<SystemTime>,
+    // 연결이 끊긴 시각. 유예 시간 안에는 같은 id로 다시 연결할 수 있다.
+    pub disconnected_at: Option<SystemTime>,
+    pub stats: Arc<ClientStats>,
+    // 연결된 동안에만 존재하는 전송 대기열
+    pub sender: Option<Arc<SendQueue>>,
+    // broadcast를 받을 topic. 처음에는 모든 topic을 구독한다.
+    pub topics: BTreeSet<Topic>,
+    // hello로 결정된 프로토콜 버전. hello를 보내지 않은 클라이언트는 1
+    pub protocol_version: u32,
+    // 이 클라이언트에게 보낼 메세지의 인코딩
+    pub encoding: Encoding,
 }
 
-pub async fn serve(ws_handler: Arc<WebSocketHandler>) -> std::result::Result<(), Box<dyn Error>> {  
+// 클라이언트와 주고받은 메세지 수
+#[derive(Debug, Default)]
+pub struct ClientStats {
+    pub received: AtomicU64,
+    pub sent: AtomicU64,
+    // 대기열이 가득 차서 버려진 메세지 수
+    pub dropped: AtomicU64,
+}
+
+impl Client {
+    // 연결되지 않은 클라이언트에게는 보내지 않는다.
+    pub fn send(&self, message: Message, policy: QueuePolicy) -> std::result::Result<(), QueueClosed> {
+        if let Some(sender) = &self.sender {
+            if sender.push(message, policy)? {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(())
+    }
+}
+
+// 클라이언트 구독 관련 이벤트 등록
+pub async fn register_events(
+    _event_bus: &Arc<EventBus>,
+    ws_handler: &Arc<WebSocketHandler>
+) -> std::result::Result<(), Box<dyn Error + Send + Sync>> {
+    let ws_handler_hello = ws_handler.clone();
+    ws_handler.register("hello", move |id, params: RequestEvent| {
+        let ws_handler = ws_handler_hello.clone();
+        async move {
+            let hello = match &params.data {
+                Some(RequestDataTypes::Hello(data)) => data.clone(),
+                _ => return Err("Hello data is required".into()),
+            };
+            log::debug!("{} hello: {:?}", id, hello);
+
+            if hello.protocol_version < MIN_PROTOCOL_VERSION {
+                let message = format!(
+                    "Protocol version {} is not supported (min: {}, current: {})",
+                    hello.protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                );
+                log::error!("{}: {}", id, message);
+                ws_handler.send_error(&id, params.request_id.clone(), ErrorCode::IncompatibleProtocol, Some(params.event.clone()), message.clone()).await?;
+                ws_handler.close(&id, message).await;
+                return Ok(());
+            }
+
+            // 클라이언트가 더 높은 버전을 사용한다면, GPA가 아는 버전으로 낮춰서 동작한다.
+            let negotiated = hello.protocol_version.min(PROTOCOL_VERSION);
+            ws_handler.set_protocol_version(&id, negotiated).await;
+            let mut info = ws_handler.hello_info().await;
+            info.negotiated_version = Some(negotiated);
+            ws_handler.send_to(id, SendEvent::from(WsEvent::Hello { info })).await?;
+            Ok(())
+        }
+    }).await?;
+
+    let ws_handler_ping = ws_handler.clone();
+    ws_handler.register("ping", move |id, params: RequestEvent| {
+        let ws_handler = ws_handler_ping.clone();
+        async move {
+            let client_time = match &params.data {
+                Some(RequestDataTypes::Ping(data)) => Some(data.client_time),
+                _ => None,
+            };
+            let server_time = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            let info = PongInfo { server_time, client_time };
+            ws_handler.send_to(id, SendEvent::from(WsEvent::Pong { info })).await?;
+            Ok(())
+        }
+    }).await?;
+
+    let ws_handler_sub = ws_handler.clone();
+    ws_handler.register("subscribe", move |id, params: RequestEvent| {
+        let ws_handler = ws_handler_sub.clone();
+        async move {
+            let topics = match &params.data {
+                Some(RequestDataTypes::TopicList(data)) => data.topics.clone(),
+                _ => return Err("Topic list is required".into()),
+            };
+            let track = topics.contains(&Topic::Track);
+            let topics = ws_handler.update_topics(&id, |t| t.extend(topics)).await;
+            ws_handler.send_to(id.clone(), SendEvent::from(WsEvent::Subscriptions { topics })).await?;
+            // 구독하지 않는 동안 놓친 값이 있으므로 trackDelta의 기준이 될 전체 값을 보낸다.
+            if track {
+                ws_handler.send_latest_track(&id).await?;
+            }
+            Ok(())
+        }
+    }).await?;
+
+    let ws_handler_unsub = ws_handler.clone();
+    ws_handler.register("unsubscribe", move |id, params: RequestEvent| {
+        let ws_handler = ws_handler_unsub.clone();
+        async move {
+            let topics = match &params.data {
+                Some(RequestDataTypes::TopicList(data)) => data.topics.clone(),
+                _ => return Err("Topic list is required".into()),
+            };
+            let topics = ws_handler.update_topics(&id, |t| t.retain(|topic| !topics.contains(topic))).await;
+            ws_handler.send_to(id, SendEvent::from(WsEvent::Subscriptions { topics })).await?;
+            Ok(())
+        }
+    }).await?;
+
+    Ok(())
+}
+
+// 모든 HTTP 라우트와 WebSocket 엔드포인트
+pub(crate) fn routes(ws_handler: Arc<WebSocketHandler>) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
     // GET /health : 서비스가 활성 상태인지 확인하기 위한 Health check 라우트.
-    let health_route = warp::path!("health").and_then(handler::health_handler);
-
-    let cors = warp::cors()
-        .allow_any_origin()
-        .allow_headers(vec![
-            "Access-Control-Allow-Origin",
-            "Access-Control-Request-Headers",
-            "Origin",
-            "Accept",
-            "X-Requested-With",
-            "Content-Type",
-        ])
-        .allow_methods(&[Method::GET, Method::POST, Method::OPTIONS])
-        .max_age(30);
+    let health_route = warp::path!("health")
+        .and(with_ws_handler(ws_handler.clone()))
+        .and_then(handler::health_handler);
+
+    // GET /schema : 프로토콜 타입들의 JSON Schema. 외부 클라이언트의 타입 생성에 사용한다.
+    let schema_route = warp::path!("schema")
+        .and(warp::get())
+        .and_then(handler::schema_handler);
+
+    // OPTIONS * : CORS preflight. 허용된 Origin에만 CORS 헤더를 돌려준다.
+    let preflight = warp::options()
+        .and(warp::header::optional::<String>("origin"))
+        .map(origin::preflight_reply);
 
+    // POST /pair : 사용자의 허용을 받아 토큰을 발급받기 위한 라우트.
+    // DELETE /pair : 요청에 사용된 토큰의 페어링을 해제하기 위한 라우트.
+    let pair = warp::path!("pair");
+    let pair_routes = pair
+        .and(warp::post())
+        .and(warp::header::optional::<String>("origin"))
+        .and(warp::body::json())
+        .and_then(handler::pair_handler)
+        .or(pair
+            .and(warp::delete())
+            .and(auth::with_token())
+            .and_then(handler::unpair_handler));
+
+    // 아래의 라우트는 모두 Authorization: Bearer <token> 헤더가 필요하다.
     // POST /reghster : ws 서비스에 client를 등록하기 위한 라우트.
     let register = warp::path("register");
     let register_routes = register
         .and(warp::post())
+        .and(auth::with_auth())
         .and(warp::body::json())
         .and(with_clients(ws_handler.clients.clone()))
+        .and(with_ws_handler(ws_handler.clone()))
         .and_then(handler::register_handler)
         // DELETE /register/{client_id} : ID를 통해 클라이언트를 등록 해제하기 위한 라우트.
         .or(register
             .and(warp::delete())
+            .and(auth::with_auth())
             .and(warp::path::param())
             .and(with_clients(ws_handler.clients.clone()))
             .and_then(handler::unregister_handler));
 
-    // POST /publish — 클라이언트들에 이벤트를 Broadcasts 하기위한 라우트.
+    // POST /publish — 클라이언트들에 이벤트를 Broadcasts 하기위한 라우트. (publish_enabled 필요)
     let publish = warp::path!("publish")
+        .and(warp::post())
+        .and(auth::with_token())
         .and(warp::body::json())
         .and(with_clients(ws_handler.clients.clone()))
         .and_then(handler::publish_handler);
 
-    // GET /ws — WebSocket 엔드포인트
+    // GET /clients — 등록된 클라이언트 목록
+    let clients_route = warp::path!("clients")
+        .and(warp::get())
+        .and(auth::with_auth())
+        .and(with_clients(ws_handler.clients.clone()))
+        .and_then(handler::clients_handler);
+
+    // GET /events?token=<token> — WebSocket을 사용할 수 없는 클라이언트를 위한 SSE 스트림
+    let events_route = warp::path!("events")
+        .and(warp::get())
+        .and(auth::with_query_auth())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(with_ws_handler(ws_handler.clone()))
+        .and_then(handler::events_handler);
+
+    // GET /track/latest — 가장 최근의 추적 결과
+    let latest_track_route = warp::path!("track" / "latest")
+        .and(warp::get())
+        .and(auth::with_auth())
+        .and(with_ws_handler(ws_handler.clone()))
+        .and_then(handler::latest_track_handler);
+
+    // GET /ws/{id}?token=<token> — WebSocket 엔드포인트
     let ws_route = warp::path("ws")
         .and(warp::ws())
         .and(warp::path::param())
+        .and(auth::with_query_auth())
         .and(with_clients(ws_handler.clients.clone()))
         .and(with_ws_handler(ws_handler.clone()))
         .and_then(handler::ws_handler);
 
     // 라우터를 등록하고, CORS를 지원하도록 함
-    let routes = health_route
-        .or(register_routes)
-        .or(ws_route)
-        .or(publish)
-        .with(cors);
-
-    // 서버 시작
-    warp::serve(routes).run(([127, 0, 0, 1], 32332)).await;
-    Ok(())
+    // 모든 라우트는 Origin 허용 목록을 거친다.
+    origin::with_origin()
+        .and(preflight
+            .or(health_route)
+            .or(schema_route)
+            .or(pair_routes)
+            .or(register_routes)
+            .or(clients_route)
+            .or(events_route)
+            .or(latest_track_route)
+            .or(ws_route)
+            .or(publish))
+        .recover(handler::handle_rejection)
+        .and(warp::header::optional::<String>("origin"))
+        .map(origin::with_cors_headers)
+}
+
+pub async fn serve(ws_handler: Arc<WebSocketHandler>) -> std::result::Result<(), Box<dyn Error>> {  
+    let routes = routes(ws_handler.clone());
+
+    // 연결하지 않고 남아있는 등록을 주기적으로 정리한다.
+    let ws_handler_purge = ws_handler.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            let config = ConfigManager::global().get().await;
+            ws_handler_purge.purge_registrations(
+                Duration::from_secs(config.register_ttl.into()),
+                Duration::from_secs(config.disconnect_grace_period.into()),
+            ).await;
+        }
+    });
+
+    // GPA의 로그를 log topic을 구독한 클라이언트에게 보낸다.
+    let ws_handler_log = ws_handler.clone();
+    let mut logs = logger::subscribe();
+    tokio::spawn(async move {
+        while let Some(info) = logs.recv().await {
+            let _ = ws_handler_log.broadcast(SendEvent::from(WsEvent::Log { info })).await;
+        }
+    });
+
+    // 서버 시작. 포트가 사용중이라면 server_port_fallback 만큼 다음 포트를 시도한다.
+    let config = ConfigManager::global().get().await;
+    let host = match config.server_host.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(e) => {
+            log::error!("잘못된 server_host: {} ({}), 127.0.0.1을 사용합니다.", config.server_host, e);
+            IpAddr::from([127, 0, 0, 1])
+        }
+    };
+    let last_port = config.server_port.saturating_add(config.server_port_fallback);
+    for port in config.server_port..=last_port {
+        match warp::serve(routes.clone()).try_bind_ephemeral((host, port)) {
+            Ok((addr, server)) => {
+                log::debug!("Server listening on {}", addr);
+                ws_handler.set_local_addr(addr).await;
+                server.await;
+                return Ok(());
+            }
+            Err(e) => log::error!("{}:{} bind 실패: {}", host, port, e),
+        }
+    }
+    Err(format!("{}:{}-{} 중 사용 가능한 포트가 없습니다.", host, config.server_port, last_port).into())
 }
 
 // 라우트에 접근했을 때, Client정보를 handler에 전달하기 위한 미들웨어(필터) 용도의 함수
@@ -92,4 +331,7 @@ fn with_ws_handler(
     ws_handler: Arc<WebSocketHandler>,
 ) -> impl Filter<Extract = (Arc<WebSocketHandler>,), Error = Infallible> + Clone {
     warp::any().map(move || ws_handler.clone())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test;
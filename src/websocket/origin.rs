@@ -0,0 +1,109 @@
+use crate::app::config::ConfigManager;
+use crate::app::get_app_state;
+use crate::views::confirm::ask_dialog;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use warp::http::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_MAX_AGE, VARY,
+};
+use warp::http::StatusCode;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+// 이번 실행 중 사용자에게 묻고 있거나, 사용자가 거부한 Origin. 같은 Origin을 반복해서 묻지 않는다.
+static ASKED_ORIGINS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+const ALLOW_HEADERS: &str = "Accept, Authorization, Content-Type, Origin, X-Requested-With";
+const ALLOW_METHODS: &str = "GET, POST, DELETE, OPTIONS";
+
+// 허용 목록에 없는 Origin의 요청
+#[derive(Debug)]
+pub struct OriginRejected;
+impl warp::reject::Reject for OriginRejected {}
+
+// 같은 Origin을 같은 문자열로 비교할 수 있도록 대소문자, 끝의 /, 기본 포트(http :80, https :443)를 정리한다.
+pub fn normalize_origin(origin: &str) -> String {
+    let origin = origin.trim().trim_end_matches('/').to_ascii_lowercase();
+    let default_port = [("http://", ":80"), ("https://", ":443")].iter()
+        .find(|(scheme, port)| origin.starts_with(scheme) && origin.ends_with(port))
+        .map(|(_, port)| port.len());
+    match default_port {
+        Some(len) => origin[..origin.len() - len].to_string(),
+        None => origin,
+    }
+}
+
+/*
+ * 모든 HTTP 라우트와 WebSocket 업그레이드 앞에 두는 필터.
+ * Origin 헤더가 없는 요청(브라우저가 아닌 로컬 프로그램)은 통과시키고,
+ * Origin 헤더가 있다면 AppConfig.allowed_origins에 있는 경우에만 통과시킨다.
+ */
+pub fn with_origin() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("origin")
+        .and_then(|origin: Option<String>| async move {
+            match origin {
+                None => Ok(()),
+                Some(origin) if get_app_state().is_origin_allowed(&origin) => Ok(()),
+                Some(origin) => {
+                    if ask_allow_origin(&origin).await {
+                        return Ok(());
+                    }
+                    log::error!("허용되지 않은 Origin의 요청: {}", origin);
+                    Err(warp::reject::custom(OriginRejected))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+// prompt_unknown_origin이 켜져있다면 사용자에게 허용 여부를 묻고, 허용하면 허용 목록에 추가한다.
+async fn ask_allow_origin(origin: &str) -> bool {
+    let config = ConfigManager::global().get().await;
+    if !config.prompt_unknown_origin || !ASKED_ORIGINS.lock().insert(origin.to_string()) {
+        return false;
+    }
+
+    let desc = format!("허용되지 않은 사이트 '{}'에서 GPA에 접근하려 합니다.\n허용 목록에 추가하시겠습니까?", origin);
+    let allowed = tokio::task::spawn_blocking(move || ask_dialog(env!("CARGO_PKG_DESCRIPTION"), &desc))
+        .await
+        .unwrap_or(false);
+    if !allowed {
+        return false;
+    }
+
+    let origin = origin.to_string();
+    ASKED_ORIGINS.lock().remove(&origin);
+    if let Err(e) = ConfigManager::global().update(|c| c.allowed_origins.push(origin.clone())).await {
+        log::error!("허용 목록 저장 실패: {}", e);
+    }
+    // 설정 저장에 실패했더라도 이번 실행 동안은 허용한다.
+    if !get_app_state().is_origin_allowed(&origin) {
+        get_app_state().allowed_origins.write().push(origin);
+    }
+    true
+}
+
+// 허용된 Origin의 CORS preflight(OPTIONS) 요청에 대한 응답
+pub fn preflight_reply(origin: Option<String>) -> Response {
+    let mut res = StatusCode::NO_CONTENT.into_response();
+    let headers = res.headers_mut();
+    headers.insert(ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static(ALLOW_METHODS));
+    headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static(ALLOW_HEADERS));
+    headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("30"));
+    with_cors_headers(res, origin)
+}
+
+// 허용된 Origin의 요청이라면 응답에 CORS 헤더를 붙인다.
+pub fn with_cors_headers(reply: impl Reply, origin: Option<String>) -> Response {
+    let mut res = reply.into_response();
+    let origin = origin
+        .filter(|o| get_app_state().is_origin_allowed(o))
+        .and_then(|o| HeaderValue::from_str(&o).ok());
+    if let Some(origin) = origin {
+        res.headers_mut().insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        res.headers_mut().insert(VARY, HeaderValue::from_static("Origin"));
+    }
+    res
+}
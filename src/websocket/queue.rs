@@ -0,0 +1,150 @@
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use tokio::sync::Notify;
+use warp::ws::Message;
+
+use crate::models::Topic;
+
+// 큐가 가득 찼을 때의 처리 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    // 가장 오래된 메세지를 버린다. 최신 값만 의미있는 track 데이터에 사용한다.
+    DropOldest,
+    // 버리지 않는다. 업데이트 진행 상황, 설정 응답 등 빠지면 안 되는 메세지에 사용한다.
+    Reliable,
+}
+
+// 쌓아둘 수 있는 Reliable 메세지 수. 넘치면 메세지를 받지 못하는 클라이언트로 보고 연결을 끊는다.
+pub const MAX_RELIABLE_MESSAGES: usize = 1024;
+
+impl From<Option<Topic>> for QueuePolicy {
+    fn from(topic: Option<Topic>) -> Self {
+        match topic {
+            Some(Topic::Track) => QueuePolicy::DropOldest,
+            _ => QueuePolicy::Reliable,
+        }
+    }
+}
+
+// 연결이 종료되어 더 이상 보낼 수 없는 큐
+#[derive(Debug)]
+pub struct QueueClosed;
+
+impl fmt::Display for QueueClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "client send queue is closed")
+    }
+}
+
+impl std::error::Error for QueueClosed {}
+
+/*
+ * 클라이언트 하나의 전송 대기열.
+ * DropOldest 메세지는 capacity개 까지만 쌓이고, 넘치면 가장 오래된 DropOldest 메세지를 버린다.
+ * Reliable 메세지는 capacity와 관계없이 MAX_RELIABLE_MESSAGES개 까지 쌓이고,
+ * 넘치면 쌓인 메세지를 버리고 close 프레임만 남긴 뒤 큐를 닫는다.
+ * 백그라운드 탭처럼 느린 클라이언트 때문에 track 데이터가 무한히 쌓이지 않도록 한다.
+ */
+#[derive(Debug)]
+pub struct SendQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+    reliable_capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: VecDeque<(Message, QueuePolicy)>,
+    droppable: usize,
+    // DropOldest 메세지를 버린 뒤 아직 전체 값을 보내지 않았다면 true
+    resync: bool,
+    closed: bool,
+}
+
+impl SendQueue {
+    pub fn new(capacity: usize) -> Self {
+        SendQueue::with_reliable_capacity(capacity, MAX_RELIABLE_MESSAGES)
+    }
+
+    pub fn with_reliable_capacity(capacity: usize, reliable_capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            capacity: capacity.max(1),
+            reliable_capacity: reliable_capacity.max(1),
+        }
+    }
+
+    // 메세지를 넣는다. 대신 버려진 메세지가 있다면 true
+    pub fn push(&self, message: Message, policy: QueuePolicy) -> Result<bool, QueueClosed> {
+        let mut state = self.state.lock();
+        if state.closed {
+            return Err(QueueClosed);
+        }
+        let mut dropped = false;
+        if policy == QueuePolicy::DropOldest {
+            if state.droppable >= self.capacity {
+                if let Some(i) = state.messages.iter().position(|(_, p)| *p == QueuePolicy::DropOldest) {
+                    state.messages.remove(i);
+                    state.droppable -= 1;
+                    state.resync = true;
+                    dropped = true;
+                }
+            }
+            state.droppable += 1;
+        } else if state.messages.len() - state.droppable >= self.reliable_capacity {
+            log::error!("Reliable 메세지가 {}개를 넘어 연결을 끊습니다.", self.reliable_capacity);
+            state.messages.clear();
+            state.droppable = 0;
+            state.messages.push_back((Message::close(), QueuePolicy::Reliable));
+            state.closed = true;
+            drop(state);
+            self.notify.notify_one();
+            return Err(QueueClosed);
+        }
+        state.messages.push_back((message, policy));
+        drop(state);
+        self.notify.notify_one();
+        Ok(dropped)
+    }
+
+    // 다음 메세지를 기다린다. 큐가 닫히고 비었다면 None
+    pub async fn pop(&self) -> Option<Message> {
+        loop {
+            {
+                let mut state = self.state.lock();
+                if let Some((message, policy)) = state.messages.pop_front() {
+                    if policy == QueuePolicy::DropOldest {
+                        state.droppable -= 1;
+                    }
+                    return Some(message);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    // 버려진 track 메세지가 있었는지 확인하고 표시를 지운다.
+    // trackDelta는 이전 값을 기준으로 하므로, true라면 다음에는 전체 값을 보내야 한다.
+    pub fn take_resync(&self) -> bool {
+        std::mem::take(&mut self.state.lock().resync)
+    }
+
+    pub fn close(&self) {
+        self.state.lock().closed = true;
+        self.notify.notify_one();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    pub fn queued(&self) -> usize {
+        self.state.lock().messages.len()
+    }
+}
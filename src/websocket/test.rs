@@ -0,0 +1,652 @@
+#[cfg(test)]
+mod queue_tests {
+    use crate::models::Topic;
+    use crate::websocket::queue::MAX_RELIABLE_MESSAGES;
+    use crate::websocket::{QueuePolicy, SendQueue};
+    use std::sync::Arc;
+    use warp::ws::Message;
+
+    #[tokio::test]
+    async fn resync_after_dropping_track() {
+        let queue = SendQueue::new(1);
+        assert!(!queue.push(Message::text("1"), QueuePolicy::DropOldest).unwrap());
+        assert!(!queue.take_resync());
+
+        assert!(queue.push(Message::text("2"), QueuePolicy::DropOldest).unwrap());
+        assert!(queue.take_resync());
+        // 한 번 확인하면 지워진다.
+        assert!(!queue.take_resync());
+        assert_eq!(queue.pop().await, Some(Message::text("2")));
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_latest_tracks() {
+        let queue = SendQueue::new(2);
+        queue.push(Message::text("config"), QueuePolicy::Reliable).unwrap();
+        let dropped: Vec<bool> = (1..=4)
+            .map(|i| queue.push(Message::text(format!("track {}", i)), QueuePolicy::DropOldest).unwrap())
+            .collect();
+        assert_eq!(dropped, vec![false, false, true, true]);
+        assert_eq!(queue.queued(), 3);
+
+        // Reliable 메세지는 버리지 않고 순서도 유지한다.
+        assert_eq!(queue.pop().await, Some(Message::text("config")));
+        assert_eq!(queue.pop().await, Some(Message::text("track 3")));
+        assert_eq!(queue.pop().await, Some(Message::text("track 4")));
+    }
+
+    #[tokio::test]
+    async fn reliable_overflow_closes_queue() {
+        let queue = SendQueue::with_reliable_capacity(1, 3);
+        queue.push(Message::text("track"), QueuePolicy::DropOldest).unwrap();
+        for i in 0..3 {
+            assert!(!queue.push(Message::text(format!("update {}", i)), QueuePolicy::Reliable).unwrap());
+        }
+        assert!(queue.push(Message::text("update 3"), QueuePolicy::Reliable).is_err());
+        assert!(queue.is_closed());
+        assert!(queue.push(Message::text("track"), QueuePolicy::DropOldest).is_err());
+
+        // 쌓인 메세지 대신 close 프레임만 보내고 끝난다.
+        assert_eq!(queue.pop().await, Some(Message::close()));
+        assert_eq!(queue.pop().await, None);
+    }
+
+    #[test]
+    fn default_reliable_capacity() {
+        let queue = SendQueue::new(1);
+        for i in 0..MAX_RELIABLE_MESSAGES {
+            queue.push(Message::text(format!("update {}", i)), QueuePolicy::Reliable).unwrap();
+        }
+        // track 메세지는 Reliable 상한과 따로 센다.
+        queue.push(Message::text("track"), QueuePolicy::DropOldest).unwrap();
+        assert!(!queue.is_closed());
+        assert!(queue.push(Message::text("overflow"), QueuePolicy::Reliable).is_err());
+        assert!(queue.is_closed());
+        assert_eq!(queue.queued(), 1);
+    }
+
+    #[test]
+    fn policy_by_topic() {
+        assert_eq!(QueuePolicy::from(Some(Topic::Track)), QueuePolicy::DropOldest);
+        for topic in [Some(Topic::Status), Some(Topic::Update), Some(Topic::Config), Some(Topic::Log), None] {
+            assert_eq!(QueuePolicy::from(topic), QueuePolicy::Reliable);
+        }
+    }
+
+    #[tokio::test]
+    async fn close_sends_remaining_messages() {
+        let queue = SendQueue::new(2);
+        queue.push(Message::text("track"), QueuePolicy::DropOldest).unwrap();
+        queue.push(Message::text("config"), QueuePolicy::Reliable).unwrap();
+        queue.close();
+        assert!(queue.push(Message::text("late"), QueuePolicy::Reliable).is_err());
+
+        assert_eq!(queue.pop().await, Some(Message::text("track")));
+        assert_eq!(queue.pop().await, Some(Message::text("config")));
+        assert_eq!(queue.pop().await, None);
+    }
+
+    #[tokio::test]
+    async fn pop_waits_for_push() {
+        let queue = Arc::new(SendQueue::new(1));
+        let popped = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.pop().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!popped.is_finished());
+        queue.push(Message::text("track"), QueuePolicy::DropOldest).unwrap();
+        assert_eq!(popped.await.unwrap(), Some(Message::text("track")));
+    }
+}
+
+// 여러 테스트에서 함께 쓰는 도우미
+#[cfg(test)]
+mod support {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use std::time::SystemTime;
+    use crate::app::get_app_state;
+    use crate::models::{Encoding, Topic};
+    use crate::websocket::{Client, SendQueue, WebSocketHandler};
+
+    // 연결된 상태의 클라이언트와 그 전송 대기열
+    pub fn connected(protocol_version: u32, encoding: Encoding) -> (Client, Arc<SendQueue>) {
+        let queue = Arc::new(SendQueue::new(8));
+        let client = Client {
+            user_id: 0,
+            name: None,
+            kind: None,
+            version: None,
+            registered_at: SystemTime::now(),
+            connected_at: Some(SystemTime::now()),
+            disconnected_at: None,
+            stats: Default::default(),
+            sender: Some(queue.clone()),
+            topics: Topic::DEFAULT.into_iter().collect::<BTreeSet<_>>(),
+            protocol_version,
+            encoding,
+        };
+        (client, queue)
+    }
+
+    // 등록만 하고 아직 연결하지 않은 클라이언트
+    pub async fn registered(ws_handler: &WebSocketHandler, id: &str) {
+        let (mut client, _) = connected(2, Encoding::Json);
+        client.connected_at = None;
+        client.sender = None;
+        ws_handler.clients.write().await.insert(id.to_string(), client);
+    }
+
+    // 허용 목록에 Origin을 추가한다. 다른 테스트와 함께 쓰는 전역 상태이므로 지우지 않는다.
+    pub fn allow_origin(origin: &str) {
+        let mut origins = get_app_state().allowed_origins.write();
+        if !origins.iter().any(|o| o == origin) {
+            origins.push(origin.to_string());
+        }
+    }
+
+    // 대기열에 쌓인 JSON 메세지의 event 이름
+    pub async fn next_event(queue: &SendQueue) -> String {
+        let message = queue.pop().await.expect("queue closed");
+        let value: serde_json::Value = serde_json::from_str(message.to_str().expect("not a text message")).unwrap();
+        value["event"].as_str().unwrap().to_string()
+    }
+}
+
+#[cfg(test)]
+mod protocol_tests {
+    use super::support::{connected, next_event};
+    use crate::models::{Encoding, RequestDataTypes, RequestEvent, SendEvent, Topic, TrackData, TrackDelta, WsEvent};
+    use crate::websocket::ws::encode;
+    use crate::websocket::WebSocketHandler;
+    use serde_json::json;
+    use warp::ws::Message;
+
+    #[tokio::test]
+    async fn v1_clients_get_full_track_instead_of_delta() {
+        let handler = WebSocketHandler::new();
+        let (v1, v1_queue) = connected(1, Encoding::Json);
+        let (v2, v2_queue) = connected(2, Encoding::Json);
+        handler.clients.write().await.insert("v1".to_string(), v1);
+        handler.clients.write().await.insert("v2".to_string(), v2);
+
+        handler.set_latest_track(Some(&TrackData { x: 1.0, y: 2.0, ..Default::default() }));
+        let delta = TrackDelta { x: Some(1.0), y: Some(2.0), ..Default::default() };
+        let summary = handler.broadcast(SendEvent::from(WsEvent::TrackDelta { data: delta })).await.unwrap();
+        assert_eq!(summary.delivered, 2);
+
+        assert_eq!(next_event(&v1_queue).await, "track");
+        assert_eq!(next_event(&v2_queue).await, "trackDelta");
+    }
+
+    #[tokio::test]
+    async fn msgpack_request_is_dispatched() {
+        let handler = WebSocketHandler::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        handler.register("msgpackTest", move |id, req: RequestEvent| {
+            let tx = tx.clone();
+            async move {
+                tx.send((id, req))?;
+                Ok(())
+            }
+        }).await.unwrap();
+
+        let frame = rmp_serde::to_vec_named(&json!({
+            "event": "msgpackTest",
+            "requestId": "7",
+            "data": { "topics": ["track", "log"] },
+        })).unwrap();
+        handler.handle_message("client", Message::binary(frame)).await.unwrap();
+
+        let (id, req) = rx.recv().await.expect("handler must be called");
+        assert_eq!(id, "client");
+        assert_eq!(req.request_id.as_deref(), Some("7"));
+        match req.data {
+            Some(RequestDataTypes::TopicList(data)) => assert_eq!(data.topics, vec![Topic::Track, Topic::Log]),
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_msgpack_request_gets_msgpack_error() {
+        let handler = WebSocketHandler::new();
+        let (client, queue) = connected(2, Encoding::Msgpack);
+        handler.clients.write().await.insert("client".to_string(), client);
+
+        handler.handle_message("client", Message::binary(vec![0xc1])).await.unwrap();
+        let message = queue.pop().await.expect("queue closed");
+        assert!(message.is_binary());
+        let error: serde_json::Value = rmp_serde::from_slice(message.as_bytes()).unwrap();
+        assert_eq!(error["event"], "error");
+    }
+
+    #[test]
+    fn msgpack_encoding_round_trip() {
+        let track = TrackData { x: 1.5, y: -2.0, a: 90.0, r: 180.0, m: 3, ..Default::default() };
+        let event = SendEvent::from(WsEvent::Track { data: track });
+        let message = encode(&event, Encoding::Msgpack).unwrap();
+        assert!(message.is_binary());
+
+        let decoded: serde_json::Value = rmp_serde::from_slice(message.as_bytes()).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&event).unwrap());
+        assert_eq!(decoded["event"], "track");
+        assert_eq!(decoded["data"]["x"], 1.5);
+    }
+}
+
+#[cfg(test)]
+mod origin_tests {
+    use super::support::allow_origin;
+    use crate::websocket::auth::PairingStore;
+    use crate::websocket::{normalize_origin, routes, WebSocketHandler};
+    use std::sync::Arc;
+    use warp::http::header::{ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN};
+    use warp::http::StatusCode;
+    use warp::reply::Response;
+    use warp::test::RequestBuilder;
+
+    const ORIGIN: &str = "https://origin-test.example";
+
+    async fn reply(request: RequestBuilder) -> Response {
+        let routes = routes(Arc::new(WebSocketHandler::new()));
+        request.filter(&routes).await.expect("request must be answered")
+    }
+
+    fn allowed_origin(res: &Response) -> Option<&str> {
+        res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).and_then(|v| v.to_str().ok())
+    }
+
+    #[tokio::test]
+    async fn with_origin_checks_allowlist() {
+        allow_origin(ORIGIN);
+        let res = reply(warp::test::request().path("/health").header("origin", ORIGIN)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(allowed_origin(&res), Some(ORIGIN));
+
+        // 대소문자, 기본 포트가 달라도 같은 Origin이다.
+        let res = reply(warp::test::request().path("/health").header("origin", "HTTPS://origin-test.example:443")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = reply(warp::test::request().path("/health").header("origin", "https://unknown.example")).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(allowed_origin(&res), None);
+
+        // 브라우저가 아닌 클라이언트는 Origin 없이 요청한다.
+        let res = reply(warp::test::request().path("/health")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(allowed_origin(&res), None);
+    }
+
+    #[tokio::test]
+    async fn preflight_only_for_allowed_origin() {
+        allow_origin(ORIGIN);
+        let preflight = |origin: &str| warp::test::request()
+            .method("OPTIONS")
+            .path("/register")
+            .header("origin", origin)
+            .header("access-control-request-method", "POST");
+
+        let res = reply(preflight(ORIGIN)).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(allowed_origin(&res), Some(ORIGIN));
+        assert!(res.headers().contains_key(ACCESS_CONTROL_ALLOW_METHODS));
+
+        let res = reply(preflight("https://unknown.example")).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(allowed_origin(&res), None);
+    }
+
+    #[test]
+    fn normalize_case_slash_and_default_port() {
+        assert_eq!(normalize_origin("HTTPS://Example.com/"), "https://example.com");
+        assert_eq!(normalize_origin("https://example.com:443"), "https://example.com");
+        assert_eq!(normalize_origin("http://localhost:80"), "http://localhost");
+        // 기본 포트가 아니라면 그대로 둔다.
+        assert_eq!(normalize_origin("https://example.com:80"), "https://example.com:80");
+        assert_eq!(normalize_origin("http://localhost:8080"), "http://localhost:8080");
+    }
+
+    #[test]
+    fn pairing_verifies_normalized_origin() {
+        let path = std::env::temp_dir().join(format!("gpa-pairings-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = PairingStore::load(path.clone());
+        let token = store.pair(Some("https://Example.com/".to_string()), None).unwrap();
+
+        assert!(store.verify(&token, Some("https://example.com")));
+        assert!(store.verify(&token, Some("HTTPS://EXAMPLE.COM:443/")));
+        assert!(!store.verify(&token, Some("https://evil.example")));
+        assert!(!store.verify(&token, None));
+        assert!(!store.verify("wrong", Some("https://example.com")));
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod auth_tests {
+    use super::support::{allow_origin, registered};
+    use crate::websocket::{routes, PairingStore, WebSocketHandler};
+    use std::sync::Arc;
+    use warp::http::StatusCode;
+    use warp::test::RequestBuilder;
+
+    const ORIGIN: &str = "https://auth-test.example";
+    const OTHER_ORIGIN: &str = "https://auth-test-other.example";
+
+    async fn status(request: RequestBuilder, ws_handler: &Arc<WebSocketHandler>) -> StatusCode {
+        request.filter(&routes(ws_handler.clone())).await.expect("request must be answered").status()
+    }
+
+    fn bearer(token: &str) -> String {
+        format!("Bearer {}", token)
+    }
+
+    // WebSocket 업그레이드 요청
+    fn upgrade(path: &str) -> RequestBuilder {
+        warp::test::request()
+            .path(path)
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+    }
+
+    #[tokio::test]
+    async fn header_token_on_track_latest() {
+        allow_origin(ORIGIN);
+        allow_origin(OTHER_ORIGIN);
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        let token = PairingStore::global().pair(Some(ORIGIN.to_string()), None).unwrap();
+        let latest = || warp::test::request().path("/track/latest").header("origin", ORIGIN);
+
+        assert_eq!(status(latest().header("authorization", bearer(&token)), &ws_handler).await, StatusCode::NO_CONTENT);
+        assert_eq!(status(latest(), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(latest().header("authorization", bearer("wrong")), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(latest().header("authorization", &token), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        // 허용된 Origin이라도 페어링한 Origin이 아니라면 거부한다.
+        let other = warp::test::request().path("/track/latest").header("origin", OTHER_ORIGIN);
+        assert_eq!(status(other.header("authorization", bearer(&token)), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        let no_origin = warp::test::request().path("/track/latest");
+        assert_eq!(status(no_origin.header("authorization", bearer(&token)), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        PairingStore::global().revoke(&token).unwrap();
+    }
+
+    #[tokio::test]
+    async fn query_token_on_events_and_ws() {
+        allow_origin(ORIGIN);
+        allow_origin(OTHER_ORIGIN);
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        registered(&ws_handler, "auth-test").await;
+        let token = PairingStore::global().pair(Some(ORIGIN.to_string()), None).unwrap();
+
+        let events = |token: &str, origin: &str| warp::test::request()
+            .path(&format!("/events?token={}", token))
+            .header("origin", origin);
+        assert_eq!(status(events(&token, ORIGIN), &ws_handler).await, StatusCode::OK);
+        assert_eq!(status(events("wrong", ORIGIN), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(events(&token, OTHER_ORIGIN), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        let no_token = warp::test::request().path("/events").header("origin", ORIGIN);
+        assert_eq!(status(no_token, &ws_handler).await, StatusCode::UNAUTHORIZED);
+
+        let ws = |token: &str, origin: &str| upgrade(&format!("/ws/auth-test?token={}", token)).header("origin", origin);
+        assert_eq!(status(ws(&token, ORIGIN), &ws_handler).await, StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(status(ws("wrong", ORIGIN), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(ws(&token, OTHER_ORIGIN), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        PairingStore::global().revoke(&token).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unpair_revokes_token() {
+        allow_origin(ORIGIN);
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        let token = PairingStore::global().pair(Some(ORIGIN.to_string()), None).unwrap();
+        let unpair = |token: &str| warp::test::request()
+            .method("DELETE")
+            .path("/pair")
+            .header("origin", ORIGIN)
+            .header("authorization", bearer(token));
+
+        assert_eq!(status(unpair("wrong"), &ws_handler).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(unpair(&token), &ws_handler).await, StatusCode::OK);
+        assert!(!PairingStore::global().verify(&token, Some(ORIGIN)));
+        let latest = warp::test::request()
+            .path("/track/latest")
+            .header("origin", ORIGIN)
+            .header("authorization", bearer(&token));
+        assert_eq!(status(latest, &ws_handler).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn pair_requires_origin_or_name() {
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        // 누구의 요청인지 알 수 없다면 사용자에게 묻지 않고 거부한다.
+        let pair = warp::test::request().method("POST").path("/pair").json(&serde_json::json!({}));
+        assert_eq!(status(pair, &ws_handler).await, StatusCode::FORBIDDEN);
+    }
+}
+
+#[cfg(test)]
+mod connection_tests {
+    use super::support::{connected, registered};
+    use crate::app::config::ConfigManager;
+    use crate::app::get_app_state;
+    use crate::models::{Encoding, Topic};
+    use crate::websocket::{routes, PairingStore, WebSocketHandler};
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    #[tokio::test(start_paused = true)]
+    async fn grace_period_stops_tracking_unless_reconnected() {
+        let config = ConfigManager::global().get().await;
+        let grace_period = Duration::from_secs(config.disconnect_grace_period.into());
+        let handler = WebSocketHandler::new();
+        get_app_state().set_tracking(true);
+
+        // 유예 시간 안에 누군가 연결하면 추적을 계속한다.
+        handler.wait_for_reconnect().await;
+        tokio::time::sleep(grace_period / 2).await;
+        handler.mark_connected();
+        tokio::time::sleep(grace_period).await;
+        assert!(get_app_state().is_tracking());
+
+        // 아무도 연결하지 않으면 유예 시간이 지난 뒤 추적을 종료한다.
+        handler.wait_for_reconnect().await;
+        tokio::time::sleep(grace_period - Duration::from_secs(1)).await;
+        assert!(get_app_state().is_tracking());
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(!get_app_state().is_tracking());
+    }
+
+    #[tokio::test]
+    async fn purge_uses_ttl_before_connect_and_grace_after_disconnect() {
+        let handler = WebSocketHandler::new();
+        let ago = |secs| SystemTime::now() - Duration::from_secs(secs);
+        let ttl = Duration::from_secs(10);
+        let grace_period = Duration::from_secs(60);
+        {
+            let mut clients = handler.clients.write().await;
+            // 연결하지 않은 등록은 ttl 기준
+            let (mut never_connected, _) = connected(2, Encoding::Json);
+            never_connected.sender = None;
+            never_connected.registered_at = ago(20);
+            clients.insert("never-connected".to_string(), never_connected);
+            // 연결이 끊긴 등록은 유예 시간 기준
+            let (mut recently_disconnected, _) = connected(2, Encoding::Json);
+            recently_disconnected.sender = None;
+            recently_disconnected.registered_at = ago(120);
+            recently_disconnected.disconnected_at = Some(ago(20));
+            clients.insert("recently-disconnected".to_string(), recently_disconnected);
+            let (mut long_disconnected, _) = connected(2, Encoding::Json);
+            long_disconnected.sender = None;
+            long_disconnected.disconnected_at = Some(ago(120));
+            clients.insert("long-disconnected".to_string(), long_disconnected);
+            // 연결된 클라이언트는 제거하지 않는다.
+            let (mut connected_client, _) = connected(2, Encoding::Json);
+            connected_client.registered_at = ago(120);
+            clients.insert("connected".to_string(), connected_client);
+        }
+
+        handler.purge_registrations(ttl, grace_period).await;
+        let remaining: BTreeSet<String> = handler.clients.read().await.keys().cloned().collect();
+        assert_eq!(remaining, ["connected", "recently-disconnected"].map(String::from).into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn reconnect_resumes_previous_id() {
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        registered(&ws_handler, "resume-test").await;
+        let token = PairingStore::global().pair(None, Some("resume-test".to_string())).unwrap();
+        let path = format!("/ws/resume-test?token={}", token);
+
+        let mut ws = warp::test::ws().path(&path).handshake(routes(ws_handler.clone())).await.unwrap();
+        let hello = ws.recv().await.unwrap();
+        assert!(hello.to_str().unwrap().contains("\"hello\""));
+        ws_handler.update_topics("resume-test", |topics| {
+            topics.clear();
+            topics.insert(Topic::Config);
+        }).await;
+        drop(ws);
+
+        // 연결이 끊겨도 등록은 남는다.
+        let disconnected = async {
+            loop {
+                let clients = ws_handler.clients.read().await;
+                let client = &clients["resume-test"];
+                if client.sender.is_none() {
+                    return client.disconnected_at;
+                }
+                drop(clients);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        let disconnected_at = tokio::time::timeout(Duration::from_secs(5), disconnected).await.unwrap();
+        assert!(disconnected_at.is_some());
+
+        // 같은 id로 다시 연결하면 구독 topic 등 이전 정보를 그대로 사용한다.
+        let mut ws = warp::test::ws().path(&path).handshake(routes(ws_handler.clone())).await.unwrap();
+        assert!(ws.recv().await.unwrap().to_str().unwrap().contains("\"hello\""));
+        {
+            let clients = ws_handler.clients.read().await;
+            let client = &clients["resume-test"];
+            assert!(client.sender.is_some());
+            assert!(client.disconnected_at.is_none());
+            assert_eq!(client.topics, BTreeSet::from([Topic::Config]));
+        }
+        PairingStore::global().revoke(&token).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use crate::models::{LogInfo, SendEvent, TrackData, WsEvent};
+    use crate::websocket::{routes, PairingStore, WebSocketHandler};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use warp::http::StatusCode;
+    use warp::hyper::body::HttpBody;
+    use warp::reply::Response;
+
+    async fn get(ws_handler: &Arc<WebSocketHandler>, path: &str, token: &str) -> Response {
+        warp::test::request()
+            .path(path)
+            .header("authorization", format!("Bearer {}", token))
+            .filter(&routes(ws_handler.clone()))
+            .await
+            .expect("request must be answered")
+    }
+
+    // SSE 응답에서 다음 이벤트 하나를 읽는다.
+    async fn next_sse(res: &mut Response) -> String {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), res.body_mut().data()).await
+            .expect("no event received")
+            .expect("stream ended")
+            .unwrap();
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    fn log_event() -> SendEvent {
+        SendEvent::from(WsEvent::Log {
+            info: LogInfo { level: "info".to_string(), target: "test".to_string(), message: "paimon".to_string(), time: 0 },
+        })
+    }
+
+    #[tokio::test]
+    async fn events_stream_subscribed_topics() {
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        let token = PairingStore::global().pair(None, Some("sse-test".to_string())).unwrap();
+
+        let mut res = get(&ws_handler, &format!("/events?token={}&topics=status", token), &token).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/event-stream");
+        // 구독하지 않은 topic의 이벤트는 건너뛴다.
+        ws_handler.broadcast(SendEvent::from(WsEvent::Track { data: TrackData::default() })).await.unwrap();
+        ws_handler.broadcast(SendEvent::from(WsEvent::DoneInit)).await.unwrap();
+        let event = next_sse(&mut res).await;
+        assert!(event.starts_with("event:doneInit\n"), "unexpected event: {}", event);
+
+        // topics가 없으면 log를 제외한 기본 topic을 보낸다.
+        let mut res = get(&ws_handler, &format!("/events?token={}", token), &token).await;
+        ws_handler.broadcast(log_event()).await.unwrap();
+        ws_handler.broadcast(SendEvent::from(WsEvent::Track { data: TrackData { x: 1.0, ..Default::default() } })).await.unwrap();
+        let event = next_sse(&mut res).await;
+        assert!(event.starts_with("event:track\n"), "unexpected event: {}", event);
+        assert!(event.contains("\"x\":1.0"));
+        PairingStore::global().revoke(&token).unwrap();
+    }
+
+    #[tokio::test]
+    async fn track_latest_returns_last_track() {
+        let ws_handler = Arc::new(WebSocketHandler::new());
+        let token = PairingStore::global().pair(None, Some("latest-test".to_string())).unwrap();
+
+        // 추적 중이 아니라면 204
+        let res = get(&ws_handler, "/track/latest", &token).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let track = TrackData { x: 1.0, y: 2.0, a: 90.0, m: 3, ..Default::default() };
+        ws_handler.set_latest_track(Some(&track));
+        let mut res = get(&ws_handler, "/track/latest", &token).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.body_mut().data().await.unwrap().unwrap();
+        let latest: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(latest, serde_json::to_value(&track).unwrap());
+        PairingStore::global().revoke(&token).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use crate::websocket::handler::{rotate_publish_audit, PUBLISH_AUDIT_MAX_SIZE};
+
+    #[test]
+    fn rotate_when_audit_exceeds_max_size() {
+        let dir = std::env::temp_dir().join(format!("gpa-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("publish-audit.jsonl");
+        let backup = dir.join("publish-audit.jsonl.1");
+
+        // 파일이 없거나 비어있다면 옮기지 않는다.
+        rotate_publish_audit(&path, PUBLISH_AUDIT_MAX_SIZE * 2).unwrap();
+        std::fs::write(&path, b"").unwrap();
+        rotate_publish_audit(&path, PUBLISH_AUDIT_MAX_SIZE * 2).unwrap();
+        assert!(path.exists() && !backup.exists());
+
+        let size = PUBLISH_AUDIT_MAX_SIZE as usize - 10;
+        std::fs::write(&path, vec![b'a'; size]).unwrap();
+        rotate_publish_audit(&path, 10).unwrap();
+        assert!(!backup.exists());
+
+        rotate_publish_audit(&path, 11).unwrap();
+        assert!(!path.exists());
+        assert_eq!(std::fs::metadata(&backup).unwrap().len(), size as u64);
+
+        // 이전 .1은 새 기록으로 덮어쓴다.
+        std::fs::write(&path, vec![b'b'; size]).unwrap();
+        rotate_publish_audit(&path, 11).unwrap();
+        assert_eq!(std::fs::read(&backup).unwrap()[0], b'b');
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
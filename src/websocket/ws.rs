@@ -1,14 +1,24 @@
-use crate::{app::get_app_state, models::{RequestEvent, SendEvent}, websocket::{Client, Clients}}
+use crate::{app::get_app_state, models::{DataTypes, Encoding, ErrorCode, ErrorInfo, HelloInfo, RequestEvent, SendEvent, Topic, TopicList, TrackData, WsEvent}, websocket::{Client, Clients, QueuePolicy, SendQueue}}
 ;
-use futures::{FutureExt, StreamExt, Future};
-use serde_json::from_str;
+use crate::app::{config::ConfigManager, path, updater::get_local_version};
+use crate::cvat::get_cvat_version;
+use crate::models::{MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use std::collections::BTreeSet;
+use futures::{SinkExt, StreamExt, Future};
+use serde_json::{from_str, Value};
 use std::error::Error;
-use tokio::sync::mpsc;
-use tokio_stream::wrappers::UnboundedReceiverStream;
 use warp::ws::{Message, WebSocket};
 use std::sync::Arc;
 use std::collections::HashMap;
-use tokio::sync::RwLock;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{broadcast, RwLock};
+
+tokio::task_local! {
+    // 처리중인 요청의 requestId. 핸들러 안에서 send_to로 보내는 모든 응답에 붙는다.
+    static REQUEST_ID: Option<String>;
+}
 
 type MessageHandler = Box<
     dyn Fn(String, RequestEvent) -> futures::future::BoxFuture<'static, Result<(), Box<dyn Error + Send + Sync>>> 
@@ -20,14 +30,65 @@ type MessageHandler = Box<
 pub struct WebSocketHandler {
     handlers: Arc<RwLock<HashMap<String, MessageHandler>>>,
     pub clients: Clients,
+    // 웹 서버가 실제로 bind된 주소
+    local_addr: Arc<RwLock<Option<SocketAddr>>>,
+    // 클라이언트가 연결될 때마다 증가한다. 재연결 대기 중 누군가 연결했는지 확인하는 데 사용한다.
+    connection_epoch: Arc<AtomicU64>,
+    // broadcast된 이벤트를 WebSocket이 아닌 구독자(SSE 등)에게 전달한다.
+    events: broadcast::Sender<StreamEvent>,
+    // 가장 최근의 추적 결과. 변경 사항만 보내는 설정이어도 항상 전체 값을 가진다.
+    latest_track: Arc<parking_lot::RwLock<Option<TrackData>>>,
+}
+
+// WebSocket 외의 구독자에게 전달되는 이벤트
+#[derive(Debug, Clone)]
+pub struct StreamEvent {
+    pub event: String,
+    pub topic: Option<Topic>,
+    // 직렬화된 SendEvent
+    pub json: String,
 }
 
 impl WebSocketHandler {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
         Self {
             handlers: Arc::new(RwLock::new(HashMap::new())),
             clients: Arc::new(RwLock::new(HashMap::new())),
+            local_addr: Arc::new(RwLock::new(None)),
+            connection_epoch: Arc::new(AtomicU64::new(0)),
+            events,
+            latest_track: Arc::new(parking_lot::RwLock::new(None)),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<StreamEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn latest_track(&self) -> Option<TrackData> {
+        self.latest_track.read().clone()
+    }
+
+    pub fn set_latest_track(&self, data: Option<&TrackData>) {
+        *self.latest_track.write() = data.cloned();
+    }
+
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.read().await
+    }
+
+    pub async fn set_local_addr(&self, addr: SocketAddr) {
+        *self.local_addr.write().await = Some(addr);
+    }
+
+    // 클라이언트가 접속할 WebSocket 주소. 0.0.0.0 등에 bind된 경우 루프백 주소로 안내한다.
+    pub async fn ws_url(&self, id: &str) -> Option<String> {
+        let mut addr = self.local_addr().await?;
+        if addr.ip().is_unspecified() {
+            addr.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
         }
+        Some(format!("ws://{}/ws/{}", addr, id))
     }
 
     pub async fn register<F, Fut>(&self, event: &str, handler: F) -> Result<(), Box<dyn Error + Send + Sync>>
@@ -46,6 +107,21 @@ impl WebSocketHandler {
     }
 
     pub async fn handle_message(&self, id: &str, msg: Message) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // msgpack 인코딩을 사용하는 클라이언트는 요청도 binary 프레임의 MessagePack으로 보낼 수 있다.
+        if msg.is_binary() {
+            log::debug!("handle_message from {} : {} bytes", id, msg.as_bytes().len());
+            let req = rmp_serde::from_slice::<Value>(msg.as_bytes())
+                .map_err(|e| e.to_string())
+                .and_then(|v| RequestEvent::from_value(v).map_err(|e| e.to_string()));
+            return match req {
+                Ok(req) => self.dispatch(id, req).await,
+                Err(e) => {
+                    log::debug!("error while parsing message to request: {}", e);
+                    self.send_error(id, None, ErrorCode::InvalidRequest, None, e).await
+                }
+            };
+        }
+
         let message = msg.to_str().map_err(|e| {
             let sanitized = msg.as_bytes()
                 .iter()
@@ -61,65 +137,321 @@ impl WebSocketHandler {
 
         log::debug!("handle_message from {} : {}", id, message);
 
-        let req: RequestEvent = match from_str(message) {
+        let req = match from_str::<Value>(message).and_then(RequestEvent::from_value) {
             Ok(v) => v,
             Err(e) => {
                 log::debug!("error while parsing message to request: {}", e);
+                // 해석할 수 없는 요청이라도 requestId는 최대한 돌려준다.
+                let request_id = from_str::<Value>(message).ok()
+                    .and_then(|v| v["requestId"].as_str().map(|s| s.to_string()));
+                self.send_error(id, request_id, ErrorCode::InvalidRequest, None, e.to_string()).await?;
                 return Ok(());
             }
         };
+        self.dispatch(id, req).await
+    }
+
+    async fn dispatch(&self, id: &str, req: RequestEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
         log::debug!("event: {}", req.event);
         log::debug!("data: {:#?}", req.data);
 
         let handlers = self.handlers.read().await;
+        let event = req.event.clone();
+        let request_id = req.request_id.clone();
 
-        if let Some(handler) = handlers.get(&req.event) {
-            log::debug!("Found handler for event: {}", req.event);
-            handler(id.to_string(), req).await
+        if let Some(handler) = handlers.get(&event) {
+            log::debug!("Found handler for event: {}", event);
+            let result = REQUEST_ID.scope(request_id.clone(), handler(id.to_string(), req)).await;
+            if let Err(e) = result {
+                log::error!("Error handling event {}: {}", event, e);
+                self.send_error(id, request_id, ErrorCode::HandlerFailed, Some(event), e.to_string()).await?;
+            }
         } else {
-            log::debug!("No handler found for event: {}", req.event);
-            Ok(())
+            log::debug!("No handler found for event: {}", event);
+            let message = format!("No handler found for event: {}", event);
+            self.send_error(id, request_id, ErrorCode::UnknownEvent, Some(event), message).await?;
         }
+        Ok(())
+    }
+
+    pub async fn send_error(
+        &self,
+        client_id: &str,
+        request_id: Option<String>,
+        code: ErrorCode,
+        event: Option<String>,
+        message: String
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // hello 이전의 클라이언트는 error 이벤트를 모르므로, requestId를 보낸 경우에만 응답한다.
+        let protocol_version = self.clients.read().await
+            .get(client_id)
+            .map_or(1, |c| c.protocol_version);
+        if protocol_version < 2 && request_id.is_none() {
+            return Ok(());
+        }
+        let mut reply = SendEvent::from(WsEvent::Error {
+            info: ErrorInfo { code, message, event }
+        });
+        reply.request_id = request_id;
+        self.send_to(client_id.to_string(), reply).await
+    }
+
+    // 등록된 요청 이벤트 이름 목록
+    pub async fn event_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.handlers.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub async fn hello_info(&self) -> HelloInfo {
+        let mut lib_version = get_cvat_version();
+        if lib_version.is_empty() {
+            lib_version = get_local_version(&path::get_lib_path());
+        }
+        HelloInfo {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            lib_version,
+            events: self.event_names().await,
+            negotiated_version: None,
+        }
+    }
+
+    pub async fn set_protocol_version(&self, client_id: &str, version: u32) {
+        if let Some(client) = self.clients.write().await.get_mut(client_id) {
+            client.protocol_version = version;
+        }
+    }
+
+    // 클라이언트에게 Close 프레임을 보내 연결을 종료시킨다.
+    pub async fn close(&self, client_id: &str, reason: String) {
+        if let Some(client) = self.clients.read().await.get(client_id) {
+            let _ = client.send(Message::close_with(1002u16, reason), QueuePolicy::Reliable);
+        }
+    }
+
+    // track을 구독하는 클라이언트에게 최신 전체 track 값을 보낸다. 추적 중이 아니라면 보내지 않는다.
+    pub async fn send_latest_track(&self, client_id: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let subscribed = self.clients.read().await
+            .get(client_id)
+            .is_some_and(|client| client.topics.contains(&Topic::Track));
+        match self.latest_track() {
+            Some(data) if subscribed => self.send_to(client_id.to_string(), SendEvent::from(WsEvent::Track { data })).await,
+            _ => Ok(()),
+        }
+    }
+
+    // 클라이언트의 구독 topic을 변경하고, 변경된 구독 목록을 돌려준다.
+    pub async fn update_topics(&self, client_id: &str, f: impl FnOnce(&mut BTreeSet<Topic>)) -> TopicList {
+        let mut clients = self.clients.write().await;
+        match clients.get_mut(client_id) {
+            Some(client) => {
+                f(&mut client.topics);
+                log::debug!("{} subscriptions: {:?}", client_id, client.topics);
+                TopicList { topics: client.topics.iter().copied().collect() }
+            }
+            None => TopicList::default(),
+        }
+    }
+
+    // 등록 후 ttl이 지나도록 연결하지 않았거나, 연결이 끊긴 뒤 grace_period 안에 재연결하지 않은 클라이언트를 제거한다.
+    pub async fn purge_registrations(&self, ttl: Duration, grace_period: Duration) {
+        self.clients.write().await.retain(|id, client| {
+            let (since, limit) = match client.disconnected_at {
+                Some(at) => (at, grace_period),
+                None => (client.registered_at, ttl),
+            };
+            let expired = client.sender.is_none()
+                && since.elapsed().is_ok_and(|elapsed| elapsed > limit);
+            if expired {
+                log::debug!("{} registration expired", id);
+            }
+            !expired
+        });
+    }
+
+    // 재연결을 기다리는 중이라면 기다림을 끝낸다.
+    pub(super) fn mark_connected(&self) {
+        self.connection_epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /*
+     * 마지막 클라이언트의 연결이 끊기면 disconnect_grace_period 동안 재연결을 기다린다.
+     * 그 동안 아무도 연결하지 않으면 추적을 종료하고,
+     * 릴리즈 빌드에서 exit_on_last_disconnect가 켜져있다면 프로세스를 종료한다.
+     */
+    pub(super) async fn wait_for_reconnect(&self) {
+        let epoch = self.connection_epoch.load(Ordering::SeqCst);
+        let config = ConfigManager::global().get().await;
+        let grace_period = Duration::from_secs(config.disconnect_grace_period.into());
+        log::debug!("No clients connected, waiting {:?} for reconnection", grace_period);
+
+        let connection_epoch = Arc::clone(&self.connection_epoch);
+        tokio::spawn(async move {
+            tokio::time::sleep(grace_period).await;
+            if connection_epoch.load(Ordering::SeqCst) != epoch {
+                return;
+            }
+            log::debug!("No clients reconnected, terminating track thread");
+            get_app_state().set_tracking(false);
+            #[cfg(not(debug_assertions))]
+            if config.exit_on_last_disconnect {
+                crate::app::terminate_process();
+            }
+        });
     }
 
-    pub async fn send_to(&self, client_id: String, event: SendEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+    pub async fn send_to(&self, client_id: String, mut event: SendEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if event.request_id.is_none() {
+            event.request_id = REQUEST_ID.try_with(|id| id.clone()).ok().flatten();
+        }
         log::debug!("send event: {:?}", event);
         if let Some(client) = self.clients.read().await.get(&client_id) {
-            if let Some(sender) = &client.sender {
-                let message = Message::text(serde_json::to_string(&event)?);
-                sender.send(Ok(message))
-                    .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
-            }
+            let message = encode(&event, client.encoding)?;
+            client.send(message, event.topic.into())
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
         }
         Ok(())
     }
 
-    pub async fn broadcast(&self, event: SendEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+    pub async fn broadcast(&self, event: SendEvent) -> Result<BroadcastSummary, Box<dyn Error + Send + Sync>> {
         log::debug!("broadcast event: {:?}", event);
         let message = Message::text(serde_json::to_string(&event)
                     .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?);
-        for (_, client) in self.clients.read().await.iter() {
-            if let Some(sender) = &client.sender {
-                sender.send(Ok(message.clone()))
-                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
-            }
+        // track 메세지를 놓친 클라이언트에게는 delta 대신 최신 전체 값을 보낸다.
+        let resync = match event.topic {
+            Some(Topic::Track) => self.latest_track().map(|data| SendEvent::from(WsEvent::Track { data })),
+            _ => None,
+        };
+        let summary = {
+            let clients = self.clients.read().await;
+            WebSocketHandler::deliver(clients.iter(), &event, &message, resync.as_ref())
+        };
+        self.prune(&summary.failed).await;
+
+        // 구독자가 없으면 실패하지만, 문제가 되지 않는다.
+        if let Ok(json) = message.to_str() {
+            let _ = self.events.send(StreamEvent {
+                event: event.event.clone(),
+                topic: event.topic,
+                json: json.to_string(),
+            });
         }
-        Ok(())
+        Ok(summary)
     }
 
-    pub async fn _broadcast_to(&self, client_ids: Vec<String>, event: SendEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+    pub async fn _broadcast_to(&self, client_ids: Vec<String>, event: SendEvent) -> Result<BroadcastSummary, Box<dyn Error + Send + Sync>> {
         let message = Message::text(serde_json::to_string(&event)
                     .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?);
-        for client_id in client_ids {
-            if let Some(sender) = self.clients.read().await.get(&client_id).and_then(|c| c.sender.as_ref()) {
-                sender.send(Ok(message.clone()))
-                    .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+        let summary = {
+            let clients = self.clients.read().await;
+            let targets = clients.iter().filter(|(id, _)| client_ids.contains(id));
+            WebSocketHandler::deliver(targets, &event, &message, None)
+        };
+        self.prune(&summary.failed).await;
+        Ok(summary)
+    }
+
+    // 한 클라이언트에게 보내지 못해도 나머지 클라이언트에게는 계속 보낸다.
+    // resync가 있다면, 대기열에서 track 메세지가 버려졌던 클라이언트와
+    // trackDelta를 모르는 프로토콜 1 클라이언트에게는 event 대신 resync를 보낸다.
+    fn deliver<'a>(
+        targets: impl Iterator<Item = (&'a String, &'a Client)>,
+        event: &SendEvent,
+        message: &Message,
+        resync: Option<&SendEvent>,
+    ) -> BroadcastSummary {
+        let mut summary = BroadcastSummary::default();
+        // msgpack은 필요한 클라이언트가 있을 때 한 번만 인코딩한다.
+        let mut msgpack: Option<Message> = None;
+        let is_delta = matches!(event.data, Some(DataTypes::TrackDelta(_)));
+        for (id, client) in targets {
+            if client.sender.is_none() || !event.topic.is_none_or(|topic| client.topics.contains(&topic)) {
+                summary.skipped += 1;
+                continue;
+            }
+            if let Some(resync) = resync.filter(|_| {
+                let missed = client.sender.as_ref().is_some_and(|q| q.take_resync());
+                missed || (is_delta && client.protocol_version < 2)
+            }) {
+                log::debug!("{} missed track messages or does not know trackDelta, sending full track", id);
+                match encode(resync, client.encoding).map(|m| client.send(m, resync.topic.into())) {
+                    Ok(Ok(_)) => summary.delivered += 1,
+                    Ok(Err(e)) => {
+                        log::debug!("broadcast to {} failed: {}", id, e);
+                        summary.failed.push(id.clone());
+                    }
+                    Err(e) => {
+                        log::error!("broadcast to {} failed: {}", id, e);
+                        summary.skipped += 1;
+                    }
+                }
+                continue;
+            }
+            let message = match client.encoding {
+                Encoding::Json => message.clone(),
+                Encoding::Msgpack => match &msgpack {
+                    Some(m) => m.clone(),
+                    None => match encode(event, Encoding::Msgpack) {
+                        Ok(m) => msgpack.insert(m).clone(),
+                        Err(e) => {
+                            log::error!("broadcast to {} failed: {}", id, e);
+                            summary.skipped += 1;
+                            continue;
+                        }
+                    },
+                },
+            };
+            match client.send(message, event.topic.into()) {
+                Ok(_) => summary.delivered += 1,
+                Err(e) => {
+                    log::debug!("broadcast to {} failed: {}", id, e);
+                    summary.failed.push(id.clone());
+                }
+            }
+        }
+        if !summary.failed.is_empty() {
+            log::debug!("broadcast {}: {:?}", event.event, summary);
+        }
+        summary
+    }
+
+    // 보내기에 실패한 클라이언트는 연결이 끊긴 것으로 보고 대기열을 정리한다.
+    async fn prune(&self, client_ids: &[String]) {
+        if client_ids.is_empty() {
+            return;
+        }
+        let mut clients = self.clients.write().await;
+        for id in client_ids {
+            if let Some(client) = clients.get_mut(id) {
+                if client.sender.as_ref().is_some_and(|q| q.is_closed()) {
+                    client.sender = None;
+                    client.disconnected_at = Some(SystemTime::now());
+                }
             }
         }
-        Ok(())
     }
 }
 
+// 클라이언트가 선택한 인코딩으로 SendEvent를 WebSocket 메세지로 만든다.
+pub fn encode(event: &SendEvent, encoding: Encoding) -> Result<Message, Box<dyn Error + Send + Sync>> {
+    Ok(match encoding {
+        Encoding::Json => Message::text(serde_json::to_string(event)?),
+        Encoding::Msgpack => Message::binary(rmp_serde::to_vec_named(event)?),
+    })
+}
+
+// broadcast 결과
+#[derive(Debug, Default)]
+pub struct BroadcastSummary {
+    pub delivered: usize,
+    // 연결되지 않았거나 topic을 구독하지 않은 클라이언트 수
+    pub skipped: usize,
+    // 보내기에 실패한 클라이언트 id
+    pub failed: Vec<String>,
+}
+
 pub async fn client_connection(
     ws: WebSocket,
     id: String,
@@ -127,49 +459,121 @@ pub async fn client_connection(
     mut client: Client,
     ws_handler: Arc<WebSocketHandler>,
 ) {    
-    let (sink, mut stream) = ws.split();
-    let (tx, rx) = mpsc::unbounded_channel();
+    let config = ConfigManager::global().get().await;
+    let (mut sink, mut stream) = ws.split();
+    let tx = Arc::new(SendQueue::new(config.send_queue_size as usize));
 
-    let rx = UnboundedReceiverStream::new(rx);
-    tokio::task::spawn(rx.forward(sink).map(|result| {
-        if let Err(e) = result {
-            log::debug!("error sending websocket msg: {}", e);
+    // 대기열의 메세지를 순서대로 보낸다. 보내기에 실패하면 대기열을 닫아 더 이상 쌓이지 않게 한다.
+    let queue = Arc::clone(&tx);
+    let stats = Arc::clone(&client.stats);
+    tokio::task::spawn(async move {
+        while let Some(message) = queue.pop().await {
+            // ping, close 같은 제어 프레임은 세지 않는다.
+            let counted = !(message.is_ping() || message.is_close());
+            if let Err(e) = sink.send(message).await {
+                log::debug!("error sending websocket msg: {}", e);
+                break;
+            }
+            if counted {
+                stats.sent.fetch_add(1, Ordering::Relaxed);
+            }
         }
-    }));
+        queue.close();
+        let _ = sink.close().await;
+    });
 
-    client.sender = Some(tx);
-    clients.write().await.insert(id.clone(), client);
+    // 같은 id로 재연결한 경우에도 구독 topic 등 이전 정보를 그대로 사용한다.
+    client.sender = Some(Arc::clone(&tx));
+    client.connected_at = Some(SystemTime::now());
+    client.disconnected_at = None;
+    clients.write().await.insert(id.clone(), client.clone());
+    ws_handler.mark_connected();
 
     log::debug!("{} connected", id);
+
+    // 접속하자마자 GPA의 버전과 지원하는 이벤트를 알린다.
+    let hello = ws_handler.hello_info().await;
+    if let Err(e) = ws_handler.send_to(id.clone(), SendEvent::from(WsEvent::Hello { info: hello })).await {
+        log::debug!("error sending hello: {}", e);
+    }
+    // 이후의 trackDelta가 기준으로 삼을 수 있도록 최신 전체 값을 보낸다.
+    if let Err(e) = ws_handler.send_latest_track(&id).await {
+        log::debug!("error sending latest track: {}", e);
+    }
     
-    while let Some(result) = stream.next().await {
-        match result {
-            Ok(msg) => {
+    // 주기적으로 ping을 보내고, pong_timeout 동안 아무것도 받지 못하면 반쯤 끊긴 연결로 보고 종료한다.
+    // pong_timeout은 ping과 별도의 타이머로 확인하므로 ping_interval이 0이어도 동작한다.
+    let pong_timeout = Duration::from_secs(config.pong_timeout.into());
+    let mut ping_interval = (config.ping_interval > 0)
+        .then(|| tokio::time::interval(Duration::from_secs(config.ping_interval.into())));
+    let mut pong_check = (!pong_timeout.is_zero())
+        .then(|| tokio::time::interval((pong_timeout / 2).max(Duration::from_secs(1))));
+    let mut last_seen = Instant::now();
+
+    loop {
+        tokio::select! {
+            result = stream.next() => {
+                let msg = match result {
+                    Some(Ok(msg)) => msg,
+                    Some(Err(e)) => {
+                        log::debug!("error receiving ws message for id: {}): {}", id.clone(), e);
+                        break;
+                    }
+                    None => break,
+                };
+                last_seen = Instant::now();
+                if msg.is_close() {
+                    break;
+                }
+                if msg.is_ping() || msg.is_pong() {
+                    continue;
+                }
+                client.stats.received.fetch_add(1, Ordering::Relaxed);
                 if let Err(e) = ws_handler.handle_message(&id, msg).await {
                     log::error!("Error handling message: {}", e);
                     break;
                 }
             }
-            Err(e) => {
-                log::debug!("error receiving ws message for id: {}): {}", id.clone(), e);
-                break;
+            _ = tick(&mut ping_interval) => {
+                let _ = tx.push(Message::ping(Vec::new()), QueuePolicy::Reliable);
             }
-        };
+            _ = tick(&mut pong_check) => {
+                if last_seen.elapsed() > pong_timeout {
+                    log::debug!("{} did not respond for {:?}, closing", id, last_seen.elapsed());
+                    let _ = tx.push(Message::close(), QueuePolicy::Reliable);
+                    break;
+                }
+            }
+        }
     }
 
-    // 클라이언트 연결이 종료되면 안전하게 제거
-    let mut clients_guard = clients.write().await;
-    clients_guard.remove(&id);
+    // 남은 메세지(close 프레임 등)를 보낸 뒤 전송 task가 종료된다.
+    tx.close();
+
+    // 클라이언트 연결이 종료되면 재연결할 수 있도록 등록은 남겨두고 연결만 해제한다.
+    // 이미 같은 id로 새로 연결된 경우라면 건드리지 않는다.
+    let any_connected = {
+        let mut clients_guard = clients.write().await;
+        if let Some(c) = clients_guard.get_mut(&id) {
+            if c.sender.as_ref().is_some_and(|s| Arc::ptr_eq(s, &tx)) {
+                c.sender = None;
+                c.disconnected_at = Some(SystemTime::now());
+            }
+        }
+        clients_guard.values().any(|c| c.sender.is_some())
+    };
     log::debug!("{} disconnected", id);
-    #[cfg(debug_assertions)]
-    if clients_guard.is_empty() {
-        // 디버그 모드에서는, 프로세스는 종료하지 않고 CVAT의 track 스레드를 종료하도록 유도함
-        log::debug!("No clients connected, terminating track thread");
-        let state = get_app_state();
-        state.set_tracking(false);
-    }
-    #[cfg(not(debug_assertions))]
-    if clients_guard.is_empty() {
-        crate::app::terminate_process();
-    }
-}
\ No newline at end of file
+    if !any_connected {
+        ws_handler.wait_for_reconnect().await;
+    }
+}
+
+// 타이머가 없다면 영원히 기다린다.
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}